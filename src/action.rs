@@ -0,0 +1,255 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+use colored::Colorize;
+use serde::Deserialize;
+
+use crate::system::System;
+
+pub const DEFAULT_ACTION_TIMEOUT_SECS: u64 = 60;
+/// Process exit code after a dry run reached the action in `exit` mode
+pub const EXIT_DRY_RUN: i32 = 3;
+
+/// Whether actions really run, and what a dry run does once they would have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DryRun {
+    /// Actions run for real
+    #[default]
+    Off,
+    /// Log the commands and keep monitoring
+    Continue,
+    /// Log the commands and exit with [`EXIT_DRY_RUN`]
+    Exit,
+}
+
+impl DryRun {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DryRun::Off => "off",
+            DryRun::Continue => "continue",
+            DryRun::Exit => "exit",
+        }
+    }
+
+    pub fn enabled(&self) -> bool { *self != DryRun::Off }
+}
+
+/// What the emergency pipeline does at one step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActionKind {
+    Poweroff,
+    Reboot,
+    Suspend,
+    Hibernate,
+    /// Shell command, e.g. to stop a service
+    RunCommand(String),
+    /// Only report that the action would have fired
+    LogOnly,
+}
+
+impl ActionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActionKind::Poweroff => "poweroff",
+            ActionKind::Reboot => "reboot",
+            ActionKind::Suspend => "suspend",
+            ActionKind::Hibernate => "hibernate",
+            ActionKind::RunCommand(_) => "run-command",
+            ActionKind::LogOnly => "log-only",
+        }
+    }
+
+    /// Power-state changes take the machine (and pingdown) down with them.
+    pub fn is_power(&self) -> bool {
+        !matches!(self, ActionKind::RunCommand(_) | ActionKind::LogOnly)
+    }
+}
+
+/// Raw action entry as written in the configuration file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ActionSpec {
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Shell command for `run-command`
+    pub command: Option<String>,
+    /// Seconds before the action is abandoned
+    #[serde(alias = "timeout-secs")]
+    pub timeout: Option<u64>,
+}
+
+impl ActionSpec {
+    /// Reads the `--action` form: a type name, or `run-command:<shell command>`.
+    pub fn from_cli(value: &str) -> Self {
+        match value.split_once(':') {
+            Some(("run-command", command)) => Self {
+                kind: "run-command".to_string(),
+                command: Some(command.to_string()),
+                timeout: None,
+            },
+            _ => Self { kind: value.to_string(), command: None, timeout: None },
+        }
+    }
+}
+
+/// One step of the emergency pipeline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Action {
+    pub kind: ActionKind,
+    pub timeout: Duration,
+}
+
+impl Action {
+    pub fn new(kind: ActionKind) -> Self {
+        Self { kind, timeout: Duration::from_secs(DEFAULT_ACTION_TIMEOUT_SECS) }
+    }
+
+    /// Validates a raw entry; the error names the offending sub-field.
+    pub fn parse(spec: &ActionSpec) -> Result<Self, (&'static str, String)> {
+        let kind = match spec.kind.trim() {
+            "run-command" => match spec.command.as_deref().map(str::trim) {
+                Some(command) if !command.is_empty() => ActionKind::RunCommand(command.to_string()),
+                _ => return Err(("command", "run-command needs a non-empty command".to_string())),
+            },
+            other => {
+                let kind = simple_kind(other).ok_or_else(|| ("type", unknown_kind(other)))?;
+                if spec.command.is_some() {
+                    return Err(("command", format!("'{}' does not take a command", other)));
+                }
+                kind
+            }
+        };
+        let timeout = match spec.timeout {
+            Some(0) => return Err(("timeout", "action timeout must be greater than zero seconds".to_string())),
+            Some(secs) => Duration::from_secs(secs),
+            None => Duration::from_secs(DEFAULT_ACTION_TIMEOUT_SECS),
+        };
+        Ok(Self { kind, timeout })
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ActionKind::RunCommand(command) => write!(f, "run-command '{}'", command),
+            kind => f.write_str(kind.as_str()),
+        }
+    }
+}
+
+fn simple_kind(name: &str) -> Option<ActionKind> {
+    match name {
+        "poweroff" => Some(ActionKind::Poweroff),
+        "reboot" => Some(ActionKind::Reboot),
+        "suspend" => Some(ActionKind::Suspend),
+        "hibernate" => Some(ActionKind::Hibernate),
+        "log-only" => Some(ActionKind::LogOnly),
+        _ => None,
+    }
+}
+
+fn unknown_kind(name: &str) -> String {
+    format!("'{}' is not an action (use poweroff, reboot, suspend, hibernate, run-command or log-only)", name)
+}
+
+/// How a single action ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActionResult {
+    Ok,
+    Failed(String),
+    TimedOut,
+    /// Only logged, because dry-run mode is on
+    DryRun,
+}
+
+/// One command tried while carrying out an action, kept for the report file so a failed
+/// shutdown can be explained afterwards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionAttempt {
+    pub command: String,
+    pub result: ActionResult,
+    /// Exit status as the OS reported it; `None` when the command never finished
+    pub status: Option<String>,
+    pub stderr: String,
+    pub elapsed: Duration,
+}
+
+impl fmt::Display for ActionAttempt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match (&self.status, &self.result) {
+            (Some(status), _) => status.clone(),
+            (None, ActionResult::TimedOut) => "timed out".to_string(),
+            (None, ActionResult::Failed(reason)) => reason.clone(),
+            (None, _) => "no status".to_string(),
+        };
+        write!(f, "'{}': {} after {:.1}s", self.command, status, self.elapsed.as_secs_f64())?;
+        match self.stderr.lines().map(str::trim).find(|line| !line.is_empty()) {
+            Some(line) => write!(f, " ({})", line),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Recorded result of one pipeline step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionOutcome {
+    pub action: Action,
+    pub result: ActionResult,
+    pub elapsed: Duration,
+    /// Every command tried for this step, in order; empty for log-only and dry runs
+    pub attempts: Vec<ActionAttempt>,
+}
+
+/// Runs every action in order, whatever the earlier ones returned, and reports each result.
+/// In a dry run nothing is executed; the commands each action would try are logged instead.
+/// `record` sees each outcome as soon as it is known, before the next action starts.
+pub fn run_actions<S: System>(
+    actions: &[Action],
+    system: &S,
+    dry_run: DryRun,
+    mut record: impl FnMut(&ActionOutcome),
+) -> Vec<ActionOutcome> {
+    let mut outcomes = Vec::with_capacity(actions.len());
+    for (idx, action) in actions.iter().enumerate() {
+        println!("{} {}/{} {}...", "[ACTION]".bold().red(), idx + 1, actions.len(), action);
+        let started = Instant::now();
+        let (result, attempts) = match action.kind {
+            ActionKind::LogOnly => (ActionResult::Ok, Vec::new()),
+            _ if dry_run.enabled() => {
+                let commands = system.action_commands(&action.kind);
+                let plan = if commands.is_empty() { "(platform default)".to_string() } else { commands.join(" || ") };
+                println!("{} would run: {} (timeout {}s)", "[DRY-RUN]".bold().cyan(), plan, action.timeout.as_secs());
+                (ActionResult::DryRun, Vec::new())
+            }
+            _ => system.run_action_recorded(&action.kind, action.timeout),
+        };
+        let elapsed = started.elapsed();
+        match &result {
+            ActionResult::Ok => println!(
+                "{} {} finished in {:.1}s",
+                "[ACTION]".bold().green(),
+                action.kind.as_str(),
+                elapsed.as_secs_f64()
+            ),
+            ActionResult::Failed(reason) => {
+                println!("{} {} failed: {}", "[ACTION]".bold().red(), action.kind.as_str(), reason)
+            }
+            ActionResult::TimedOut => println!(
+                "{} {} timed out after {}s",
+                "[ACTION]".bold().red(),
+                action.kind.as_str(),
+                action.timeout.as_secs()
+            ),
+            ActionResult::DryRun => {}
+        }
+        if matches!(result, ActionResult::Failed(_) | ActionResult::TimedOut) {
+            for attempt in &attempts {
+                println!("{}   tried {}", "[ACTION]".bold().red(), attempt);
+            }
+        }
+        let outcome = ActionOutcome { action: action.clone(), result, elapsed, attempts };
+        record(&outcome);
+        outcomes.push(outcome);
+    }
+    outcomes
+}
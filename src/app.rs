@@ -2,10 +2,11 @@ use std::sync::{Arc, Mutex};
 use std::sync::atomic::AtomicBool;
 use clap::Parser;
 
-use crate::cli::Cli;
+use crate::cli::{Cli, Command};
 use crate::config::{build_monitor_config, OutputInfo};
+use crate::doctor;
 use crate::monitor::normal_loop;
-use crate::runtime::Metrics;
+use crate::runtime::{process_started, Metrics};
 use crate::signals::install_ctrlc_handler;
 use crate::system::{error, DefaultSystem, System};
 
@@ -28,20 +29,24 @@ impl<S: System + Send + Sync + 'static> App<S> {
     /// until a shutdown signal is received (Ctrl-C), upon which final metrics are flushed
     /// and the process exits.
     pub fn run(self) {
-        // 1) Parse CLI
+        // 1) Parse CLI (and pin the process start for the startup grace period)
+        process_started();
         let cli = Cli::parse();
+        if let Some(Command::Doctor { .. }) = cli.command {
+            std::process::exit(doctor::run(&cli, &self.system));
+        }
 
-        // 2) Prepare runtime state and graceful-shutdown signal wiring
-        let metrics = Arc::new(Mutex::new(Metrics::new()));
-        let shutdown_flag = Arc::new(AtomicBool::new(false));
-        install_ctrlc_handler(shutdown_flag.clone(), metrics.clone());
-
-        // 3) Resolve configuration from JSON or CLI
+        // 2) Resolve configuration from JSON or CLI
         let config = match build_monitor_config(&cli) {
             Ok(cfg) => cfg,
             Err(err) => error(&format!("resolving configuration\n{}", err)),
         };
 
+        // 3) Prepare runtime state and graceful-shutdown signal wiring
+        let metrics = Arc::new(Mutex::new(Metrics::new()));
+        let shutdown_flag = Arc::new(AtomicBool::new(false));
+        install_ctrlc_handler(shutdown_flag.clone(), metrics.clone(), config.templates.clone());
+
         // 4) Perform platform-specific console tweaks (no-op on Unix)
         self.system.console_setup();
 
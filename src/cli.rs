@@ -1,4 +1,4 @@
-use clap::{ArgAction, Parser, ValueHint};
+use clap::{ArgAction, Parser, Subcommand, ValueHint};
 use std::path::PathBuf;
 
 #[derive(Parser, Debug, Clone)]
@@ -10,7 +10,9 @@ use std::path::PathBuf;
     long_about = "Pingdown continuously pings one or more targets and reports their status.\n\
     It supports 'any-success' (default) and 'strict all-success' modes, configurable \n\
     normal/emergency intervals, optional progress indicator, and rich CLI output.",
-    after_help = "EXAMPLES:\n  # Check one target every 60s (default)\n  pingdown 8.8.8.8\n\n  # Strict mode, two targets, normal=30s, emergency=10s, attempts=5\n  pingdown -s -n 30 -e 10 -t 5 1.1.1.1 8.8.8.8\n\n  # Use configuration file\n  pingdown --config ./config.json\n\n  # Quiet summary-only output, show progress spinner\n  pingdown --status-only --progress 1.1.1.1\n"
+    after_help = "EXAMPLES:\n  # Check one target every 60s (default)\n  pingdown 8.8.8.8\n\n  # Strict mode, two targets, normal=30s, emergency=10s, attempts=5\n  pingdown -s -n 30 -e 10 -t 5 1.1.1.1 8.8.8.8\n\n  # Use configuration file\n  pingdown --config ./config.json\n\n  # Quiet summary-only output, show progress spinner\n  pingdown --status-only --progress 1.1.1.1\n\n  # Check the environment before deploying\n  pingdown --config ./config.json doctor\n",
+    // `pingdown <TARGETS> doctor` runs the doctor on those targets
+    subcommand_precedence_over_arg = true
 )]
 pub struct Cli {
     /// Target IP address(es) or domain name(s) to check
@@ -23,13 +25,17 @@ pub struct Cli {
     pub strict: bool,
 
     /// Read configuration from a JSON file (same format as README).
-    #[arg(short = 'c', long = "config", value_name = "FILE")]
+    #[arg(short = 'c', long = "config", value_name = "FILE", global = true)]
     pub config: Option<PathBuf>,
 
     /// Deprecated: read ./config.json from current directory. Use --config instead.
     #[arg(short = 'r', long = "read-json", hide = false)]
     pub read_json: bool,
 
+    /// Declare that a target is only reachable through another, e.g. 8.8.8.8=@gateway (repeatable)
+    #[arg(long = "depends", value_name = "CHILD=PARENT")]
+    pub depends: Vec<String>,
+
     /// Interval (in seconds) between regular checks (default: 60)
     #[arg(short = 'n', long = "normal", value_name = "SECS")]
     pub normal_interval: Option<u64>,
@@ -42,12 +48,162 @@ pub struct Cli {
     #[arg(short = 't', long = "tries", value_name = "NUM")]
     pub emergency_retries: Option<u32>,
 
-    /// Increase output verbosity (-v, -vv). Conflicts with --quiet and --status-only
-    #[arg(short = 'v', long = "verbose", action = ArgAction::Count, conflicts_with_all = ["quiet", "status_only"])]
+    /// Consecutive failed checks required before entering emergency mode (default: 1)
+    #[arg(long = "fail-threshold", value_name = "NUM")]
+    pub fail_threshold: Option<u32>,
+
+    /// Consecutive successful checks required before leaving emergency mode (default: 1)
+    #[arg(long = "recover-threshold", value_name = "NUM")]
+    pub recover_threshold: Option<u32>,
+
+    /// Sliding window (in seconds) for flap detection (0 disables, the default)
+    #[arg(long = "flap-window", value_name = "SECS")]
+    pub flap_window: Option<u64>,
+
+    /// Transitions inside --flap-window that mark the link as flapping (default: 4)
+    #[arg(long = "flap-threshold", value_name = "NUM")]
+    pub flap_threshold: Option<u32>,
+
+    /// What to do with the action while the link is flapping: run it as usual (none, the
+    /// default), skip it (suppress) or hold it until the link is stable (defer)
+    #[arg(long = "flap-action", value_name = "ACTION", value_parser = ["none", "suppress", "defer"])]
+    pub flap_action: Option<String>,
+
+    /// When to run the action: after consecutive failed checks and --tries (consecutive, the
+    /// default) or when availability over --availability-window drops below the threshold
+    #[arg(long = "policy", value_name = "POLICY", value_parser = ["consecutive", "availability"])]
+    pub policy: Option<String>,
+
+    /// Sliding window (in seconds) the availability policy is evaluated over (default: 600)
+    #[arg(long = "availability-window", value_name = "SECS")]
+    pub availability_window: Option<u64>,
+
+    /// Minimum availability in percent before the availability policy acts (default: 90)
+    #[arg(long = "availability-threshold", value_name = "PCT")]
+    pub availability_threshold: Option<u8>,
+
+    /// Count whole check cycles (cycles, the default) or single target probes (probes) as samples
+    #[arg(long = "availability-basis", value_name = "BASIS", value_parser = ["cycles", "probes"])]
+    pub availability_basis: Option<String>,
+
+    /// Fire the emergency action once connectivity has been down this long, instead of after --tries
+    #[arg(long = "max-outage", value_name = "SECS")]
+    pub max_outage: Option<u64>,
+
+    /// Emergency action to run, in order (repeatable): poweroff, reboot, suspend, hibernate,
+    /// log-only or run-command:<CMD>. Defaults to poweroff
+    #[arg(long = "action", value_name = "ACTION")]
+    pub actions: Vec<String>,
+
+    /// Command that may repair the network, tried in order before the actions run (repeatable).
+    /// pingdown re-probes 10 seconds after each one and stands down if connectivity is back
+    #[arg(long = "remediate", value_name = "CMD")]
+    pub remediate: Vec<String>,
+
+    /// Run at most this many poweroff/reboot/suspend/hibernate actions per 24 hours, counted
+    /// across restarts in pingdown_state.json; later ones are only logged (0 disables)
+    #[arg(long = "action-limit", value_name = "NUM")]
+    pub action_limit: Option<u32>,
+
+    /// Log the commands actions would run instead of running them; then keep monitoring
+    /// (continue, the default) or exit with code 3 (exit)
+    #[arg(
+        long = "dry-run",
+        value_name = "MODE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "continue",
+        value_parser = ["continue", "exit"]
+    )]
+    pub dry_run: Option<String>,
+
+    /// Script to run before the actions (repeatable). It gets the outage as PINGDOWN_* environment
+    /// variables and is killed after 30 seconds
+    #[arg(long = "pre-action-hook", value_name = "CMD")]
+    pub pre_action_hooks: Vec<String>,
+
+    /// Like --pre-action-hook, but a non-zero exit cancels the actions (repeatable)
+    #[arg(long = "veto-hook", value_name = "CMD")]
+    pub veto_hooks: Vec<String>,
+
+    /// Script to run on a state change, as EVENT:CMD (repeatable). Events: startup, emergency,
+    /// retry (each failed emergency check) and recovery
+    #[arg(long = "hook", value_name = "EVENT:CMD")]
+    pub hooks: Vec<String>,
+
+    /// URL that receives a JSON POST on startup, emergency, action and recovery (repeatable).
+    /// Failed deliveries are retried 3 times with backoff
+    #[arg(long = "webhook", value_name = "URL")]
+    pub webhooks: Vec<String>,
+
+    /// Keep webhook notifications that could not be delivered in this file and send them
+    /// once connectivity returns
+    #[arg(long = "webhook-queue", value_name = "PATH")]
+    pub webhook_queue: Option<PathBuf>,
+
+    /// Mail relay for alerts on emergency, action and recovery, as HOST[:PORT]. Credentials are
+    /// read from the "smtp" section of the config file
+    #[arg(long = "smtp", value_name = "HOST[:PORT]")]
+    pub smtp: Option<String>,
+
+    /// Upgrade the SMTP connection with STARTTLS (port 587 unless given)
+    #[arg(long = "smtp-starttls")]
+    pub smtp_starttls: bool,
+
+    /// Sender address of alert mails
+    #[arg(long = "mail-from", value_name = "ADDR")]
+    pub mail_from: Option<String>,
+
+    /// Recipient of alert mails (repeatable)
+    #[arg(long = "mail-to", value_name = "ADDR")]
+    pub mail_to: Vec<String>,
+
+    /// MQTT broker to publish the state and per-target status to after every check, as HOST[:PORT]
+    #[arg(long = "mqtt", value_name = "HOST[:PORT]")]
+    pub mqtt: Option<String>,
+
+    /// Topic prefix for --mqtt [default: pingdown/<host name>]
+    #[arg(long = "mqtt-prefix", value_name = "PREFIX")]
+    pub mqtt_prefix: Option<String>,
+
+    /// Replace a built-in message template, as NAME=TEXT (repeatable). Names: start, normal,
+    /// flapping, degraded, emergency, countdown, availability, emergency-enter,
+    /// emergency-remaining, action, recovery, summary, wall-countdown, wall-cancel,
+    /// mail-subject and mail-body
+    #[arg(long = "template", value_name = "NAME=TEXT")]
+    pub templates: Vec<String>,
+
+    /// Announce the action to logged-in users and wait this many seconds before running it,
+    /// cancelling if connectivity returns (0 runs it immediately, at most 86400)
+    #[arg(long = "countdown", value_name = "SECS")]
+    pub countdown: Option<u64>,
+
+    /// Never run the emergency action during this many seconds after start (0 disables)
+    #[arg(long = "startup-grace", value_name = "SECS")]
+    pub startup_grace: Option<u64>,
+
+    /// Randomly move each check by up to this percentage of its interval
+    #[arg(long = "jitter", value_name = "PCT")]
+    pub jitter: Option<u8>,
+
+    /// Randomly move each check by up to this many seconds (at most 3600) instead of a percentage
+    #[arg(long = "jitter-secs", value_name = "SECS")]
+    pub jitter_secs: Option<u64>,
+
+    /// Seed the jitter generator so the sequence is reproducible (random per process by default)
+    #[arg(long = "jitter-seed", value_name = "SEED")]
+    pub jitter_seed: Option<u64>,
+
+    /// Wait a random delay of up to this many seconds before the first check
+    #[arg(long = "initial-offset", value_name = "SECS")]
+    pub initial_offset: Option<u64>,
+
+    /// Increase output verbosity (-v, -vv). Ignored for per-target logs under --quiet or --status-only
+    #[arg(short = 'v', long = "verbose", action = ArgAction::Count)]
     pub verbose: u8,
 
-    /// Suppress per-target messages; only summaries are printed. Conflicts with --verbose
-    #[arg(short = 'q', long = "quiet", conflicts_with = "verbose")]
+    /// Suppress per-target messages; only summaries are printed
+    #[arg(short = 'q', long = "quiet")]
     pub quiet: bool,
 
     /// Only print structured status summaries (no per-target ping logs)
@@ -57,4 +213,21 @@ pub struct Cli {
     /// Show a progress spinner while waiting between checks
     #[arg(long = "progress", default_value_t = false)]
     pub progress: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Check that this machine can run pingdown, then exit
+    ///
+    /// Checks the ping binary, ICMP privileges, DNS for configured hostnames, the shutdown
+    /// privilege, the metrics file location and the configuration.
+    /// Exit code: 0 all checks passed, 1 warnings only, 2 at least one failure.
+    Doctor {
+        /// Targets to check instead of those given before the subcommand or in the config file
+        #[arg(value_name = "TARGETS", value_hint = ValueHint::Hostname)]
+        targets: Vec<String>,
+    },
 }
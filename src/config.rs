@@ -1,6 +1,7 @@
 use colored::Colorize;
 use regex::Regex;
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::env;
 use std::fmt::Debug;
 use std::fs;
@@ -9,21 +10,145 @@ use std::num::NonZeroU32;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 
+use crate::action::{Action, ActionKind, ActionSpec, DryRun};
 use crate::cli::Cli;
+use crate::escalation::{EscalationStage, StageSpec};
+use crate::flap::FlapAction;
+use crate::hook::{Hook, HookSpec, TransitionHook, TransitionHookSpec};
+use crate::jitter::JitterSpread;
+use crate::mail::{Smtp, SmtpSpec};
+use crate::mqtt::{Mqtt, MqttSpec};
+use crate::remediation::{RemediationSpec, RemediationStep};
+use crate::schedule::{PolicyProfile, ProfileSpec, TimeWindow, TimeWindowSpec};
+use crate::template::Templates;
+use crate::webhook::{Webhook, WebhookSpec};
 
 pub const ENV_CONFIG_PATH: &str = "PINGDOWN_CONFIG";
 pub const DEFAULT_CONFIG_PATH: &str = "./config.json";
 pub const DEFAULT_NORMAL_SECS: u64 = 60;
 pub const DEFAULT_EMERGENCY_SECS: u64 = 20;
 pub const DEFAULT_EMERGENCY_RETRIES: u32 = 3;
+pub const DEFAULT_FAIL_THRESHOLD: u32 = 1;
+pub const DEFAULT_RECOVER_THRESHOLD: u32 = 1;
+pub const DEFAULT_FLAP_THRESHOLD: u32 = 4;
+pub const DEFAULT_AVAILABILITY_WINDOW_SECS: u64 = 600;
+pub const DEFAULT_AVAILABILITY_THRESHOLD: u8 = 90;
+pub const DEFAULT_ACTION_LIMIT_WINDOW_SECS: u64 = 24 * 60 * 60;
+/// Longest countdown accepted, in seconds
+pub const MAX_COUNTDOWN_SECS: u64 = 24 * 60 * 60;
+/// Widest fixed jitter spread accepted, in seconds
+pub const MAX_JITTER_SECS: u64 = 60 * 60;
+/// Pseudo-target resolved to the default IPv4 gateway at probe time
+pub const GATEWAY_TARGET: &str = "@gateway";
+
+/// Decides when connectivity loss escalates to the emergency action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DecisionPolicy {
+    /// Escalate after consecutive failed cycles and exhausted emergency retries
+    #[default]
+    Consecutive,
+    /// Escalate when availability over a sliding window drops below a threshold
+    Availability,
+}
+
+/// Clock the startup grace period is measured against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraceClock {
+    /// Time since pingdown started
+    #[default]
+    Process,
+    /// Time since the system booted (/proc/uptime); falls back to process time elsewhere
+    Uptime,
+}
+
+impl GraceClock {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GraceClock::Process => "process start",
+            GraceClock::Uptime => "system uptime",
+        }
+    }
+}
+
+/// What the availability policy counts as a sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AvailabilityBasis {
+    /// One sample per monitoring cycle, successful when the cycle passed
+    #[default]
+    Cycles,
+    /// One sample per target probe
+    Probes,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MonitorConfig {
     pub targets: Vec<String>,
+    /// Child target -> parent it is only reachable through
+    pub dependencies: BTreeMap<String, String>,
     pub strict: bool,
     pub normal_interval: Duration,
     pub emergency_interval: Duration,
     pub emergency_retries: NonZeroU32,
+    /// Consecutive failed cycles required before entering the emergency loop
+    pub fail_threshold: NonZeroU32,
+    /// Consecutive successful cycles required before leaving the emergency loop
+    pub recover_threshold: NonZeroU32,
+    /// Sliding window for flap detection; zero disables it
+    pub flap_window: Duration,
+    /// Transitions inside `flap_window` that mark the link as flapping
+    pub flap_threshold: u32,
+    pub flap_action: FlapAction,
+    pub policy: DecisionPolicy,
+    /// Sliding window the availability policy is evaluated over
+    pub availability_window: Duration,
+    /// Minimum availability in percent before the availability policy escalates
+    pub availability_threshold: u8,
+    pub availability_basis: AvailabilityBasis,
+    /// Continuous outage after which the emergency action fires, replacing the retry count
+    pub max_outage: Option<Duration>,
+    /// Steps run in order once the emergency action is due
+    pub actions: Vec<Action>,
+    /// Ladder of emergency stages; when set, each stage's pacing and actions replace the base ones in turn
+    pub escalation: Vec<EscalationStage>,
+    /// Repairs tried, with a re-probe after each, before the actions run
+    pub remediation: Vec<RemediationStep>,
+    /// Most power actions allowed within `action_limit_window`; later ones are only logged
+    pub action_limit: Option<NonZeroU32>,
+    pub action_limit_window: Duration,
+    /// Log actions instead of running them
+    pub dry_run: DryRun,
+    /// Scripts run just before the actions, any of which may veto them
+    pub pre_action_hooks: Vec<Hook>,
+    /// Scripts run on startup, entering emergency, failed emergency checks and recovery
+    pub transition_hooks: Vec<TransitionHook>,
+    /// Endpoints that receive a JSON POST on startup, emergency, action and recovery
+    pub webhooks: Vec<Webhook>,
+    /// File that keeps webhook deliveries which ran out of retries until they can be sent
+    pub webhook_queue: Option<PathBuf>,
+    /// Mail relay that alerts are sent through on emergency, action and recovery
+    pub smtp: Option<Smtp>,
+    /// Broker that the state and per-target status are published to after every cycle
+    pub mqtt: Option<Mqtt>,
+    /// Console, wall and mail templates, with the user's overrides
+    pub templates: Templates,
+    /// Warning period between the action falling due and running it; users are notified
+    pub countdown: Option<Duration>,
+    /// Local-time windows during which the emergency action is never run
+    pub maintenance_windows: Vec<TimeWindow>,
+    /// Time-of-day overrides; the first matching profile wins
+    pub profiles: Vec<PolicyProfile>,
+    /// Period after start during which failures never escalate to the emergency action
+    pub startup_grace: Option<Duration>,
+    pub grace_clock: GraceClock,
+    /// Random spread applied to normal and emergency sleeps
+    pub jitter: JitterSpread,
+    /// Fixed seed for the jitter generator; random per process when unset
+    pub jitter_seed: Option<u64>,
+    /// Upper bound of a random delay before the first check
+    pub initial_offset: Duration,
     pub quiet: bool,
     pub status_only: bool,
     pub progress: bool,
@@ -42,6 +167,98 @@ impl MonitorConfig {
     pub fn emergency_retry_attempts(&self) -> u32 {
         self.emergency_retries.get()
     }
+
+    pub fn fail_threshold_cycles(&self) -> u32 {
+        self.fail_threshold.get()
+    }
+
+    pub fn recover_threshold_cycles(&self) -> u32 {
+        self.recover_threshold.get()
+    }
+
+    /// Effective configuration while escalation stage `stage` is active.
+    pub fn with_stage(&self, stage: &EscalationStage) -> MonitorConfig {
+        let mut effective = self.clone();
+        effective.emergency_interval = stage.emergency_interval;
+        effective.emergency_retries = stage.emergency_retries;
+        effective.actions = stage.actions.clone();
+        effective
+    }
+
+    /// Effective configuration with `profile`'s overrides applied on top of this one.
+    pub fn with_profile(&self, profile: &PolicyProfile) -> MonitorConfig {
+        let mut effective = self.clone();
+        if let Some(value) = profile.normal_interval {
+            effective.normal_interval = value;
+        }
+        if let Some(value) = profile.emergency_interval {
+            effective.emergency_interval = value;
+        }
+        if let Some(value) = profile.emergency_retries {
+            effective.emergency_retries = value;
+        }
+        if let Some(value) = profile.strict {
+            effective.strict = value;
+        }
+        effective
+    }
+}
+
+impl Default for MonitorConfig {
+    fn default() -> Self {
+        Self {
+            targets: Vec::new(),
+            dependencies: BTreeMap::new(),
+            strict: false,
+            normal_interval: Duration::from_secs(DEFAULT_NORMAL_SECS),
+            emergency_interval: Duration::from_secs(DEFAULT_EMERGENCY_SECS),
+            emergency_retries: NonZeroU32::new(DEFAULT_EMERGENCY_RETRIES).expect("non-zero default"),
+            fail_threshold: NonZeroU32::new(DEFAULT_FAIL_THRESHOLD).expect("non-zero default"),
+            recover_threshold: NonZeroU32::new(DEFAULT_RECOVER_THRESHOLD).expect("non-zero default"),
+            flap_window: Duration::ZERO,
+            flap_threshold: DEFAULT_FLAP_THRESHOLD,
+            flap_action: FlapAction::None,
+            policy: DecisionPolicy::Consecutive,
+            availability_window: Duration::from_secs(DEFAULT_AVAILABILITY_WINDOW_SECS),
+            availability_threshold: DEFAULT_AVAILABILITY_THRESHOLD,
+            availability_basis: AvailabilityBasis::Cycles,
+            max_outage: None,
+            actions: vec![Action::new(ActionKind::Poweroff)],
+            escalation: Vec::new(),
+            remediation: Vec::new(),
+            action_limit: None,
+            action_limit_window: Duration::from_secs(DEFAULT_ACTION_LIMIT_WINDOW_SECS),
+            pre_action_hooks: Vec::new(),
+            transition_hooks: Vec::new(),
+            webhooks: Vec::new(),
+            webhook_queue: None,
+            smtp: None,
+            mqtt: None,
+            templates: Templates::default(),
+            dry_run: DryRun::Off,
+            countdown: None,
+            maintenance_windows: Vec::new(),
+            profiles: Vec::new(),
+            startup_grace: None,
+            grace_clock: GraceClock::Process,
+            jitter: JitterSpread::None,
+            jitter_seed: None,
+            initial_offset: Duration::ZERO,
+            quiet: false,
+            status_only: false,
+            progress: false,
+            verbose: 0,
+        }
+    }
+}
+
+impl AvailabilityBasis {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AvailabilityBasis::Cycles => "cycles",
+            AvailabilityBasis::Probes => "probes",
+        }
+    }
 }
 
 pub trait ConfigLoader {
@@ -59,7 +276,7 @@ impl ConfigLoader for JsonConfigLoader {
 }
 
 pub fn build_monitor_config(cli: &Cli) -> Result<MonitorConfig, ConfigError> {
-    build_monitor_config_with_loader(cli, &JsonConfigLoader::default())
+    build_monitor_config_with_loader(cli, &JsonConfigLoader)
 }
 
 pub fn build_monitor_config_with_loader(
@@ -98,9 +315,11 @@ fn resolve_config_path(cli: &Cli) -> Option<PathBuf> {
 
 #[derive(Debug, Deserialize, Default)]
 #[serde(default)]
-struct FileConfig {
+pub struct FileConfig {
     #[serde(alias = "address", alias = "addresses")]
     targets: Option<Vec<String>>,
+    #[serde(alias = "depends-on")]
+    dependencies: Option<BTreeMap<String, String>>,
     strict: Option<bool>,
     #[serde(alias = "secs-for-normal-loop")]
     normal_secs: Option<u64>,
@@ -108,6 +327,59 @@ struct FileConfig {
     emergency_secs: Option<u64>,
     #[serde(alias = "times-for-emergency-loop")]
     emergency_retries: Option<u32>,
+    #[serde(alias = "fail-threshold")]
+    fail_threshold: Option<u32>,
+    #[serde(alias = "recover-threshold")]
+    recover_threshold: Option<u32>,
+    #[serde(alias = "flap-window")]
+    flap_window: Option<u64>,
+    #[serde(alias = "flap-threshold")]
+    flap_threshold: Option<u32>,
+    #[serde(alias = "flap-action")]
+    flap_action: Option<FlapAction>,
+    policy: Option<DecisionPolicy>,
+    #[serde(alias = "availability-window")]
+    availability_window: Option<u64>,
+    #[serde(alias = "availability-threshold")]
+    availability_threshold: Option<u8>,
+    #[serde(alias = "availability-basis")]
+    availability_basis: Option<AvailabilityBasis>,
+    #[serde(alias = "max-outage")]
+    max_outage: Option<u64>,
+    actions: Option<Vec<ActionSpec>>,
+    escalation: Option<Vec<StageSpec>>,
+    remediation: Option<Vec<RemediationSpec>>,
+    #[serde(alias = "action-limit")]
+    action_limit: Option<u32>,
+    #[serde(alias = "action-limit-window")]
+    action_limit_window: Option<u64>,
+    #[serde(alias = "dry-run")]
+    dry_run: Option<DryRun>,
+    #[serde(alias = "pre-action-hooks")]
+    pre_action_hooks: Option<Vec<HookSpec>>,
+    hooks: Option<Vec<TransitionHookSpec>>,
+    webhooks: Option<Vec<WebhookSpec>>,
+    #[serde(alias = "webhook-queue")]
+    webhook_queue: Option<PathBuf>,
+    smtp: Option<SmtpSpec>,
+    mqtt: Option<MqttSpec>,
+    templates: Option<BTreeMap<String, String>>,
+    countdown: Option<u64>,
+    #[serde(alias = "maintenance-windows")]
+    maintenance_windows: Option<Vec<TimeWindowSpec>>,
+    profiles: Option<Vec<ProfileSpec>>,
+    #[serde(alias = "startup-grace")]
+    startup_grace: Option<u64>,
+    #[serde(alias = "grace-clock")]
+    grace_clock: Option<GraceClock>,
+    #[serde(alias = "jitter-percent")]
+    jitter_percent: Option<u8>,
+    #[serde(alias = "jitter-secs")]
+    jitter_secs: Option<u64>,
+    #[serde(alias = "jitter-seed")]
+    jitter_seed: Option<u64>,
+    #[serde(alias = "initial-offset")]
+    initial_offset: Option<u64>,
     quiet: Option<bool>,
     #[serde(alias = "status_only", alias = "status-only")]
     status_only: Option<bool>,
@@ -118,10 +390,43 @@ struct FileConfig {
 #[derive(Debug, Default)]
 struct MonitorConfigBuilder {
     targets: Option<FieldValue<Vec<String>>>,
+    dependencies: Option<FieldValue<BTreeMap<String, String>>>,
     strict: Option<FieldValue<bool>>,
     normal_secs: Option<FieldValue<u64>>,
     emergency_secs: Option<FieldValue<u64>>,
     emergency_retries: Option<FieldValue<u32>>,
+    fail_threshold: Option<FieldValue<u32>>,
+    recover_threshold: Option<FieldValue<u32>>,
+    flap_window: Option<FieldValue<u64>>,
+    flap_threshold: Option<FieldValue<u32>>,
+    flap_action: Option<FieldValue<FlapAction>>,
+    policy: Option<FieldValue<DecisionPolicy>>,
+    availability_window: Option<FieldValue<u64>>,
+    availability_threshold: Option<FieldValue<u8>>,
+    availability_basis: Option<FieldValue<AvailabilityBasis>>,
+    max_outage: Option<FieldValue<u64>>,
+    actions: Option<FieldValue<Vec<ActionSpec>>>,
+    escalation: Option<FieldValue<Vec<StageSpec>>>,
+    remediation: Option<FieldValue<Vec<RemediationSpec>>>,
+    action_limit: Option<FieldValue<u32>>,
+    action_limit_window: Option<FieldValue<u64>>,
+    dry_run: Option<FieldValue<DryRun>>,
+    pre_action_hooks: Option<FieldValue<Vec<HookSpec>>>,
+    transition_hooks: Option<FieldValue<Vec<TransitionHookSpec>>>,
+    webhooks: Option<FieldValue<Vec<WebhookSpec>>>,
+    webhook_queue: Option<FieldValue<PathBuf>>,
+    smtp: Option<FieldValue<SmtpSpec>>,
+    mqtt: Option<FieldValue<MqttSpec>>,
+    templates: Option<FieldValue<BTreeMap<String, String>>>,
+    countdown: Option<FieldValue<u64>>,
+    maintenance_windows: Option<FieldValue<Vec<TimeWindowSpec>>>,
+    profiles: Option<FieldValue<Vec<ProfileSpec>>>,
+    startup_grace: Option<FieldValue<u64>>,
+    grace_clock: Option<FieldValue<GraceClock>>,
+    jitter_percent: Option<FieldValue<u8>>,
+    jitter_secs: Option<FieldValue<u64>>,
+    jitter_seed: Option<FieldValue<u64>>,
+    initial_offset: Option<FieldValue<u64>>,
     quiet: Option<FieldValue<bool>>,
     status_only: Option<FieldValue<bool>>,
     progress: Option<FieldValue<bool>>,
@@ -135,6 +440,9 @@ impl MonitorConfigBuilder {
         if let Some(targets) = cfg.targets {
             self.targets = Some(FieldValue::new(targets, prefix("address")));
         }
+        if let Some(dependencies) = cfg.dependencies {
+            self.dependencies = Some(FieldValue::new(dependencies, prefix("dependencies")));
+        }
         if let Some(strict) = cfg.strict {
             self.strict = Some(FieldValue::new(strict, prefix("strict")));
         }
@@ -147,6 +455,102 @@ impl MonitorConfigBuilder {
         if let Some(value) = cfg.emergency_retries {
             self.emergency_retries = Some(FieldValue::new(value, prefix("times-for-emergency-loop")));
         }
+        if let Some(value) = cfg.fail_threshold {
+            self.fail_threshold = Some(FieldValue::new(value, prefix("fail-threshold")));
+        }
+        if let Some(value) = cfg.recover_threshold {
+            self.recover_threshold = Some(FieldValue::new(value, prefix("recover-threshold")));
+        }
+        if let Some(value) = cfg.flap_window {
+            self.flap_window = Some(FieldValue::new(value, prefix("flap-window")));
+        }
+        if let Some(value) = cfg.flap_threshold {
+            self.flap_threshold = Some(FieldValue::new(value, prefix("flap-threshold")));
+        }
+        if let Some(value) = cfg.flap_action {
+            self.flap_action = Some(FieldValue::new(value, prefix("flap-action")));
+        }
+        if let Some(value) = cfg.policy {
+            self.policy = Some(FieldValue::new(value, prefix("policy")));
+        }
+        if let Some(value) = cfg.availability_window {
+            self.availability_window = Some(FieldValue::new(value, prefix("availability-window")));
+        }
+        if let Some(value) = cfg.availability_threshold {
+            self.availability_threshold = Some(FieldValue::new(value, prefix("availability-threshold")));
+        }
+        if let Some(value) = cfg.availability_basis {
+            self.availability_basis = Some(FieldValue::new(value, prefix("availability-basis")));
+        }
+        if let Some(value) = cfg.max_outage {
+            self.max_outage = Some(FieldValue::new(value, prefix("max-outage")));
+        }
+        if let Some(value) = cfg.actions {
+            self.actions = Some(FieldValue::new(value, prefix("actions")));
+        }
+        if let Some(value) = cfg.escalation {
+            self.escalation = Some(FieldValue::new(value, prefix("escalation")));
+        }
+        if let Some(value) = cfg.remediation {
+            self.remediation = Some(FieldValue::new(value, prefix("remediation")));
+        }
+        if let Some(value) = cfg.action_limit {
+            self.action_limit = Some(FieldValue::new(value, prefix("action-limit")));
+        }
+        if let Some(value) = cfg.action_limit_window {
+            self.action_limit_window = Some(FieldValue::new(value, prefix("action-limit-window")));
+        }
+        if let Some(value) = cfg.dry_run {
+            self.dry_run = Some(FieldValue::new(value, prefix("dry-run")));
+        }
+        if let Some(value) = cfg.pre_action_hooks {
+            self.pre_action_hooks = Some(FieldValue::new(value, prefix("pre-action-hooks")));
+        }
+        if let Some(value) = cfg.hooks {
+            self.transition_hooks = Some(FieldValue::new(value, prefix("hooks")));
+        }
+        if let Some(value) = cfg.webhooks {
+            self.webhooks = Some(FieldValue::new(value, prefix("webhooks")));
+        }
+        if let Some(value) = cfg.webhook_queue {
+            self.webhook_queue = Some(FieldValue::new(value, prefix("webhook-queue")));
+        }
+        if let Some(value) = cfg.smtp {
+            self.smtp = Some(FieldValue::new(value, prefix("smtp")));
+        }
+        if let Some(value) = cfg.mqtt {
+            self.mqtt = Some(FieldValue::new(value, prefix("mqtt")));
+        }
+        if let Some(value) = cfg.templates {
+            self.templates = Some(FieldValue::new(value, prefix("templates")));
+        }
+        if let Some(value) = cfg.countdown {
+            self.countdown = Some(FieldValue::new(value, prefix("countdown")));
+        }
+        if let Some(value) = cfg.maintenance_windows {
+            self.maintenance_windows = Some(FieldValue::new(value, prefix("maintenance-windows")));
+        }
+        if let Some(value) = cfg.profiles {
+            self.profiles = Some(FieldValue::new(value, prefix("profiles")));
+        }
+        if let Some(value) = cfg.startup_grace {
+            self.startup_grace = Some(FieldValue::new(value, prefix("startup-grace")));
+        }
+        if let Some(value) = cfg.grace_clock {
+            self.grace_clock = Some(FieldValue::new(value, prefix("grace-clock")));
+        }
+        if let Some(value) = cfg.jitter_percent {
+            self.jitter_percent = Some(FieldValue::new(value, prefix("jitter-percent")));
+        }
+        if let Some(value) = cfg.jitter_secs {
+            self.jitter_secs = Some(FieldValue::new(value, prefix("jitter-secs")));
+        }
+        if let Some(value) = cfg.jitter_seed {
+            self.jitter_seed = Some(FieldValue::new(value, prefix("jitter-seed")));
+        }
+        if let Some(value) = cfg.initial_offset {
+            self.initial_offset = Some(FieldValue::new(value, prefix("initial-offset")));
+        }
         if let Some(value) = cfg.quiet {
             self.quiet = Some(FieldValue::new(value, prefix("quiet")));
         }
@@ -165,6 +569,18 @@ impl MonitorConfigBuilder {
         if !cli.targets.is_empty() {
             self.targets = Some(FieldValue::new(cli.targets.clone(), "cli.targets".to_string()));
         }
+        if !cli.depends.is_empty() {
+            // An entry without '=' keeps an empty parent and is rejected in build()
+            let dependencies = cli
+                .depends
+                .iter()
+                .map(|entry| match entry.split_once('=') {
+                    Some((child, parent)) => (child.trim().to_string(), parent.trim().to_string()),
+                    None => (entry.trim().to_string(), String::new()),
+                })
+                .collect();
+            self.dependencies = Some(FieldValue::new(dependencies, "cli --depends".to_string()));
+        }
         if cli.strict {
             self.strict = Some(FieldValue::new(true, "cli --strict/-s".to_string()));
         }
@@ -177,6 +593,138 @@ impl MonitorConfigBuilder {
         if let Some(value) = cli.emergency_retries {
             self.emergency_retries = Some(FieldValue::new(value, "cli --tries/-t".to_string()));
         }
+        if let Some(value) = cli.fail_threshold {
+            self.fail_threshold = Some(FieldValue::new(value, "cli --fail-threshold".to_string()));
+        }
+        if let Some(value) = cli.recover_threshold {
+            self.recover_threshold = Some(FieldValue::new(value, "cli --recover-threshold".to_string()));
+        }
+        if let Some(value) = cli.flap_window {
+            self.flap_window = Some(FieldValue::new(value, "cli --flap-window".to_string()));
+        }
+        if let Some(value) = cli.flap_threshold {
+            self.flap_threshold = Some(FieldValue::new(value, "cli --flap-threshold".to_string()));
+        }
+        if let Some(value) = cli.flap_action.as_deref() {
+            let action = match value {
+                "suppress" => FlapAction::Suppress,
+                "defer" => FlapAction::Defer,
+                _ => FlapAction::None,
+            };
+            self.flap_action = Some(FieldValue::new(action, "cli --flap-action".to_string()));
+        }
+        if let Some(value) = cli.policy.as_deref() {
+            let policy = if value == "availability" { DecisionPolicy::Availability } else { DecisionPolicy::Consecutive };
+            self.policy = Some(FieldValue::new(policy, "cli --policy".to_string()));
+        }
+        if let Some(value) = cli.availability_window {
+            self.availability_window = Some(FieldValue::new(value, "cli --availability-window".to_string()));
+        }
+        if let Some(value) = cli.availability_threshold {
+            self.availability_threshold = Some(FieldValue::new(value, "cli --availability-threshold".to_string()));
+        }
+        if let Some(value) = cli.availability_basis.as_deref() {
+            let basis = if value == "probes" { AvailabilityBasis::Probes } else { AvailabilityBasis::Cycles };
+            self.availability_basis = Some(FieldValue::new(basis, "cli --availability-basis".to_string()));
+        }
+        if let Some(value) = cli.max_outage {
+            self.max_outage = Some(FieldValue::new(value, "cli --max-outage".to_string()));
+        }
+        if !cli.actions.is_empty() {
+            let specs = cli.actions.iter().map(|value| ActionSpec::from_cli(value)).collect();
+            self.actions = Some(FieldValue::new(specs, "cli --action".to_string()));
+        }
+        if !cli.remediate.is_empty() {
+            let specs = cli
+                .remediate
+                .iter()
+                .map(|command| RemediationSpec { command: command.clone(), ..RemediationSpec::default() })
+                .collect();
+            self.remediation = Some(FieldValue::new(specs, "cli --remediate".to_string()));
+        }
+        if let Some(value) = cli.action_limit {
+            self.action_limit = Some(FieldValue::new(value, "cli --action-limit".to_string()));
+        }
+        if let Some(value) = cli.dry_run.as_deref() {
+            let mode = if value == "exit" { DryRun::Exit } else { DryRun::Continue };
+            self.dry_run = Some(FieldValue::new(mode, "cli --dry-run".to_string()));
+        }
+        if !cli.pre_action_hooks.is_empty() || !cli.veto_hooks.is_empty() {
+            let plain = cli.pre_action_hooks.iter().map(|command| (command, false));
+            let veto = cli.veto_hooks.iter().map(|command| (command, true));
+            let specs = plain
+                .chain(veto)
+                .map(|(command, veto)| HookSpec { command: command.clone(), timeout: None, veto })
+                .collect();
+            self.pre_action_hooks = Some(FieldValue::new(specs, "cli --pre-action-hook".to_string()));
+        }
+        if !cli.hooks.is_empty() {
+            let specs = cli.hooks.iter().map(|value| TransitionHookSpec::from_cli(value)).collect();
+            self.transition_hooks = Some(FieldValue::new(specs, "cli --hook".to_string()));
+        }
+        if !cli.webhooks.is_empty() {
+            let specs = cli.webhooks.iter().map(|url| WebhookSpec { url: url.clone(), ..WebhookSpec::default() }).collect();
+            self.webhooks = Some(FieldValue::new(specs, "cli --webhook".to_string()));
+        }
+        if let Some(value) = &cli.webhook_queue {
+            self.webhook_queue = Some(FieldValue::new(value.clone(), "cli --webhook-queue".to_string()));
+        }
+        // The mail flags patch the file's smtp section, so credentials can stay in the file
+        if cli.smtp.is_some() || cli.smtp_starttls || cli.mail_from.is_some() || !cli.mail_to.is_empty() {
+            let mut spec = self.smtp.take().map(|field| field.value).unwrap_or_default();
+            if let Some(server) = &cli.smtp {
+                spec.server = server.clone();
+                spec.port = None;
+            }
+            spec.starttls |= cli.smtp_starttls;
+            if let Some(from) = &cli.mail_from {
+                spec.from = from.clone();
+            }
+            if !cli.mail_to.is_empty() {
+                spec.to = cli.mail_to.clone();
+            }
+            self.smtp = Some(FieldValue::new(spec, "cli --smtp".to_string()));
+        }
+        if cli.mqtt.is_some() || cli.mqtt_prefix.is_some() {
+            let mut spec = self.mqtt.take().map(|field| field.value).unwrap_or_default();
+            if let Some(broker) = &cli.mqtt {
+                spec.broker = broker.clone();
+                spec.port = None;
+            }
+            if let Some(prefix) = &cli.mqtt_prefix {
+                spec.prefix = Some(prefix.clone());
+            }
+            self.mqtt = Some(FieldValue::new(spec, "cli --mqtt".to_string()));
+        }
+        if !cli.templates.is_empty() {
+            // Overrides single templates on top of the file's; an entry without '=' keeps an
+            // empty template and is rejected in build()
+            let mut templates = self.templates.take().map(|field| field.value).unwrap_or_default();
+            for entry in &cli.templates {
+                match entry.split_once('=') {
+                    Some((name, source)) => templates.insert(name.trim().to_string(), source.to_string()),
+                    None => templates.insert(entry.trim().to_string(), String::new()),
+                };
+            }
+            self.templates = Some(FieldValue::new(templates, "cli --template".to_string()));
+        }
+        if let Some(value) = cli.countdown {
+            self.countdown = Some(FieldValue::new(value, "cli --countdown".to_string()));
+        }
+        if let Some(value) = cli.startup_grace {
+            self.startup_grace = Some(FieldValue::new(value, "cli --startup-grace".to_string()));
+        }
+        if cli.jitter.is_some() || cli.jitter_secs.is_some() {
+            // A CLI spread replaces any spread from the file
+            self.jitter_percent = cli.jitter.map(|value| FieldValue::new(value, "cli --jitter".to_string()));
+            self.jitter_secs = cli.jitter_secs.map(|value| FieldValue::new(value, "cli --jitter-secs".to_string()));
+        }
+        if let Some(value) = cli.jitter_seed {
+            self.jitter_seed = Some(FieldValue::new(value, "cli --jitter-seed".to_string()));
+        }
+        if let Some(value) = cli.initial_offset {
+            self.initial_offset = Some(FieldValue::new(value, "cli --initial-offset".to_string()));
+        }
         if cli.quiet {
             self.quiet = Some(FieldValue::new(true, "cli --quiet/-q".to_string()));
         }
@@ -210,7 +758,7 @@ impl MonitorConfigBuilder {
             .expect("valid address regex");
 
         for (idx, target) in targets.iter().enumerate() {
-            if !re_address.is_match(target) {
+            if target != GATEWAY_TARGET && !re_address.is_match(target) {
                 return Err(ConfigError::validation(
                     format!("{}[{}]", targets_path, idx),
                     format!("'{}' is not a valid host, IP, or URL", target),
@@ -218,6 +766,14 @@ impl MonitorConfigBuilder {
             }
         }
 
+        let dependencies = match self.dependencies {
+            Some(FieldValue { value, path }) => {
+                validate_dependencies(&value, &targets, &re_address, &path)?;
+                value
+            }
+            None => BTreeMap::new(),
+        };
+
         let (normal_secs, normal_path) = match self.normal_secs {
             Some(FieldValue { value, path }) => (value, Some(path)),
             None => (DEFAULT_NORMAL_SECS, None),
@@ -254,6 +810,201 @@ impl MonitorConfigBuilder {
             )
         })?;
 
+        let (fail_value, fail_path) = match self.fail_threshold {
+            Some(FieldValue { value, path }) => (value, Some(path)),
+            None => (DEFAULT_FAIL_THRESHOLD, None),
+        };
+
+        let fail_threshold = NonZeroU32::new(fail_value).ok_or_else(|| {
+            ConfigError::validation(
+                fail_path.unwrap_or_else(|| "defaults.fail_threshold".to_string()),
+                "fail threshold must be at least 1 cycle",
+            )
+        })?;
+
+        let (recover_value, recover_path) = match self.recover_threshold {
+            Some(FieldValue { value, path }) => (value, Some(path)),
+            None => (DEFAULT_RECOVER_THRESHOLD, None),
+        };
+
+        let recover_threshold = NonZeroU32::new(recover_value).ok_or_else(|| {
+            ConfigError::validation(
+                recover_path.unwrap_or_else(|| "defaults.recover_threshold".to_string()),
+                "recover threshold must be at least 1 cycle",
+            )
+        })?;
+
+        let flap_window = self.flap_window.map(|FieldValue { value, .. }| value).unwrap_or(0);
+        let (flap_threshold, flap_threshold_path) = match self.flap_threshold {
+            Some(FieldValue { value, path }) => (value, Some(path)),
+            None => (DEFAULT_FLAP_THRESHOLD, None),
+        };
+
+        if flap_window > 0 && flap_threshold < 2 {
+            return Err(ConfigError::validation(
+                flap_threshold_path.unwrap_or_else(|| "defaults.flap_threshold".to_string()),
+                "flap threshold must be at least 2 transitions",
+            ));
+        }
+
+        let flap_action = self.flap_action.map(|FieldValue { value, .. }| value).unwrap_or_default();
+
+        let policy = self.policy.map(|FieldValue { value, .. }| value).unwrap_or_default();
+
+        let (availability_window, availability_window_path) = match self.availability_window {
+            Some(FieldValue { value, path }) => (value, Some(path)),
+            None => (DEFAULT_AVAILABILITY_WINDOW_SECS, None),
+        };
+
+        if availability_window == 0 {
+            return Err(ConfigError::validation(
+                availability_window_path.unwrap_or_else(|| "defaults.availability_window".to_string()),
+                "availability window must be greater than zero seconds",
+            ));
+        }
+
+        let (availability_threshold, availability_threshold_path) = match self.availability_threshold {
+            Some(FieldValue { value, path }) => (value, Some(path)),
+            None => (DEFAULT_AVAILABILITY_THRESHOLD, None),
+        };
+
+        if availability_threshold == 0 || availability_threshold > 100 {
+            return Err(ConfigError::validation(
+                availability_threshold_path.unwrap_or_else(|| "defaults.availability_threshold".to_string()),
+                "availability threshold must be between 1 and 100 percent",
+            ));
+        }
+
+        let availability_basis = self.availability_basis.map(|FieldValue { value, .. }| value).unwrap_or_default();
+
+        let max_outage = match self.max_outage {
+            Some(FieldValue { value: 0, path }) => {
+                return Err(ConfigError::validation(path, "max outage must be greater than zero seconds"));
+            }
+            Some(FieldValue { value, .. }) => Some(Duration::from_secs(value)),
+            None => None,
+        };
+
+        let actions = match self.actions {
+            Some(FieldValue { value, path }) => parse_actions(&value, &path)?,
+            None => vec![Action::new(ActionKind::Poweroff)],
+        };
+        let escalation = match self.escalation {
+            Some(FieldValue { path, .. }) if policy == DecisionPolicy::Availability => {
+                return Err(ConfigError::validation(path, "escalation stages need the consecutive policy"));
+            }
+            Some(FieldValue { value, path }) => {
+                parse_escalation(&value, &path, Duration::from_secs(emergency_secs), emergency_retries)?
+            }
+            None => Vec::new(),
+        };
+        let remediation = match self.remediation {
+            Some(FieldValue { value, path }) => parse_remediation(&value, &path)?,
+            None => Vec::new(),
+        };
+        // Zero explicitly disables the limit
+        let action_limit = self.action_limit.and_then(|FieldValue { value, .. }| NonZeroU32::new(value));
+        let action_limit_window = match self.action_limit_window {
+            Some(FieldValue { value: 0, path }) => {
+                return Err(ConfigError::validation(path, "action limit window must be greater than zero seconds"));
+            }
+            Some(FieldValue { value, .. }) => Duration::from_secs(value),
+            None => Duration::from_secs(DEFAULT_ACTION_LIMIT_WINDOW_SECS),
+        };
+        let dry_run = self.dry_run.map(|FieldValue { value, .. }| value).unwrap_or_default();
+        let pre_action_hooks = match self.pre_action_hooks {
+            Some(FieldValue { value, path }) => parse_hooks(&value, &path)?,
+            None => Vec::new(),
+        };
+        let transition_hooks = match self.transition_hooks {
+            Some(FieldValue { value, path }) => parse_transition_hooks(&value, &path)?,
+            None => Vec::new(),
+        };
+        let webhooks = match self.webhooks {
+            Some(FieldValue { value, path }) => parse_webhooks(&value, &path)?,
+            None => Vec::new(),
+        };
+        let smtp = match self.smtp {
+            Some(FieldValue { value, path }) => Some(
+                Smtp::parse(&value)
+                    .map_err(|(field, message)| ConfigError::validation(format!("{}.{}", path, field), message))?,
+            ),
+            None => None,
+        };
+        let mqtt = match self.mqtt {
+            Some(FieldValue { value, path }) => Some(
+                Mqtt::parse(&value)
+                    .map_err(|(field, message)| ConfigError::validation(format!("{}.{}", path, field), message))?,
+            ),
+            None => None,
+        };
+        let templates = match self.templates {
+            Some(FieldValue { value, path }) => Templates::new(value)
+                .map_err(|(name, message)| ConfigError::validation(format!("{}.{}", path, name), message))?,
+            None => Templates::default(),
+        };
+        let webhook_queue = match self.webhook_queue {
+            Some(FieldValue { value, path }) if value.as_os_str().is_empty() => {
+                return Err(ConfigError::validation(path, "webhook queue path cannot be empty"));
+            }
+            Some(FieldValue { value, .. }) => Some(value),
+            None => None,
+        };
+
+        let maintenance_windows = match self.maintenance_windows {
+            Some(FieldValue { value, path }) => parse_time_windows(&value, &path)?,
+            None => Vec::new(),
+        };
+
+        let profiles = match self.profiles {
+            Some(FieldValue { value, path }) => parse_profiles(&value, &path)?,
+            None => Vec::new(),
+        };
+
+        // Zero explicitly disables the grace period
+        let startup_grace = self
+            .startup_grace
+            .map(|FieldValue { value, .. }| value)
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs);
+        let grace_clock = self.grace_clock.map(|FieldValue { value, .. }| value).unwrap_or_default();
+        if let Some(FieldValue { value, path }) = &self.countdown {
+            if *value > MAX_COUNTDOWN_SECS {
+                return Err(ConfigError::validation(
+                    path.clone(),
+                    format!("countdown must be at most {} seconds", MAX_COUNTDOWN_SECS),
+                ));
+            }
+        }
+        // Zero runs the actions immediately, as without a countdown
+        let countdown = self
+            .countdown
+            .map(|FieldValue { value, .. }| value)
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs);
+
+        let jitter = match (self.jitter_percent, self.jitter_secs) {
+            (Some(_), Some(FieldValue { path, .. })) => {
+                return Err(ConfigError::validation(path, "set either jitter-percent or jitter-secs, not both"));
+            }
+            (Some(FieldValue { value, path }), None) => {
+                if value > 100 {
+                    return Err(ConfigError::validation(path, "jitter percentage must be between 0 and 100"));
+                }
+                if value == 0 { JitterSpread::None } else { JitterSpread::Percent(value) }
+            }
+            (None, Some(FieldValue { value, path })) if value > MAX_JITTER_SECS => {
+                return Err(ConfigError::validation(
+                    path,
+                    format!("jitter spread must be at most {} seconds", MAX_JITTER_SECS),
+                ));
+            }
+            (None, Some(FieldValue { value, .. })) if value > 0 => JitterSpread::Fixed(Duration::from_secs(value)),
+            _ => JitterSpread::None,
+        };
+        let jitter_seed = self.jitter_seed.map(|FieldValue { value, .. }| value);
+        let initial_offset = Duration::from_secs(self.initial_offset.map(|FieldValue { value, .. }| value).unwrap_or(0));
+
         let strict = self.strict.map(|FieldValue { value, .. }| value).unwrap_or(false);
         let quiet = self.quiet.map(|FieldValue { value, .. }| value).unwrap_or(false);
         let status_only = self.status_only.map(|FieldValue { value, .. }| value).unwrap_or(false);
@@ -262,10 +1013,42 @@ impl MonitorConfigBuilder {
 
         Ok(MonitorConfig {
             targets,
+            dependencies,
             strict,
             normal_interval: Duration::from_secs(normal_secs),
             emergency_interval: Duration::from_secs(emergency_secs),
             emergency_retries,
+            fail_threshold,
+            recover_threshold,
+            flap_window: Duration::from_secs(flap_window),
+            flap_threshold,
+            flap_action,
+            policy,
+            availability_window: Duration::from_secs(availability_window),
+            availability_threshold,
+            availability_basis,
+            max_outage,
+            actions,
+            escalation,
+            remediation,
+            action_limit,
+            action_limit_window,
+            dry_run,
+            pre_action_hooks,
+            transition_hooks,
+            webhooks,
+            webhook_queue,
+            smtp,
+            mqtt,
+            templates,
+            countdown,
+            maintenance_windows,
+            profiles,
+            startup_grace,
+            grace_clock,
+            jitter,
+            jitter_seed,
+            initial_offset,
             quiet,
             status_only,
             progress,
@@ -274,6 +1057,184 @@ impl MonitorConfigBuilder {
     }
 }
 
+/// Children must be monitored targets, parents valid hosts or `@gateway`, and chains acyclic.
+fn validate_dependencies(
+    dependencies: &BTreeMap<String, String>,
+    targets: &[String],
+    re_address: &Regex,
+    path: &str,
+) -> Result<(), ConfigError> {
+    for (child, parent) in dependencies {
+        let entry_path = format!("{}.{}", path, child);
+        if !targets.contains(child) {
+            return Err(ConfigError::validation(entry_path, format!("'{}' is not a monitored target", child)));
+        }
+        if parent.is_empty() {
+            return Err(ConfigError::validation(entry_path, "a parent target is required (CHILD=PARENT)"));
+        }
+        if parent != GATEWAY_TARGET && !re_address.is_match(parent) {
+            return Err(ConfigError::validation(
+                entry_path,
+                format!("'{}' is not a valid host, IP, URL or {}", parent, GATEWAY_TARGET),
+            ));
+        }
+        let mut seen = vec![child.as_str()];
+        let mut current = parent.as_str();
+        loop {
+            if seen.contains(&current) {
+                return Err(ConfigError::validation(entry_path, format!("dependency cycle through '{}'", current)));
+            }
+            seen.push(current);
+            match dependencies.get(current) {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_actions(specs: &[ActionSpec], path: &str) -> Result<Vec<Action>, ConfigError> {
+    if specs.is_empty() {
+        return Err(ConfigError::validation(path, "at least one action is required (use log-only to only report)"));
+    }
+    specs
+        .iter()
+        .enumerate()
+        .map(|(idx, spec)| {
+            Action::parse(spec)
+                .map_err(|(field, message)| ConfigError::validation(format!("{}[{}].{}", path, idx, field), message))
+        })
+        .collect()
+}
+
+/// Unset stage intervals and tries fall back to the base emergency settings.
+fn parse_escalation(
+    specs: &[StageSpec],
+    path: &str,
+    emergency_interval: Duration,
+    emergency_retries: NonZeroU32,
+) -> Result<Vec<EscalationStage>, ConfigError> {
+    let mut stages = Vec::with_capacity(specs.len());
+    for (idx, spec) in specs.iter().enumerate() {
+        let field = |name: &str| format!("{}[{}].{}", path, idx, name);
+        let name = match spec.name.as_deref().map(str::trim) {
+            Some("") => return Err(ConfigError::validation(field("name"), "stage name cannot be empty")),
+            Some(name) => name.to_string(),
+            None => format!("stage {}", idx + 1),
+        };
+        let emergency_interval = match spec.interval {
+            Some(0) => {
+                return Err(ConfigError::validation(field("interval"), "stage interval must be greater than zero seconds"));
+            }
+            Some(secs) => Duration::from_secs(secs),
+            None => emergency_interval,
+        };
+        let emergency_retries = match spec.tries {
+            Some(value) => NonZeroU32::new(value)
+                .ok_or_else(|| ConfigError::validation(field("tries"), "stage tries must be at least 1"))?,
+            None => emergency_retries,
+        };
+        let actions = parse_actions(&spec.actions, &field("actions"))?;
+        stages.push(EscalationStage { name, emergency_interval, emergency_retries, actions });
+    }
+    Ok(stages)
+}
+
+fn parse_remediation(specs: &[RemediationSpec], path: &str) -> Result<Vec<RemediationStep>, ConfigError> {
+    specs
+        .iter()
+        .enumerate()
+        .map(|(idx, spec)| {
+            RemediationStep::parse(spec)
+                .map_err(|(field, message)| ConfigError::validation(format!("{}[{}].{}", path, idx, field), message))
+        })
+        .collect()
+}
+
+fn parse_webhooks(specs: &[WebhookSpec], path: &str) -> Result<Vec<Webhook>, ConfigError> {
+    specs
+        .iter()
+        .enumerate()
+        .map(|(idx, spec)| {
+            Webhook::parse(spec)
+                .map_err(|(field, message)| ConfigError::validation(format!("{}[{}].{}", path, idx, field), message))
+        })
+        .collect()
+}
+
+fn parse_hooks(specs: &[HookSpec], path: &str) -> Result<Vec<Hook>, ConfigError> {
+    specs
+        .iter()
+        .enumerate()
+        .map(|(idx, spec)| {
+            Hook::parse(spec)
+                .map_err(|(field, message)| ConfigError::validation(format!("{}[{}].{}", path, idx, field), message))
+        })
+        .collect()
+}
+
+fn parse_transition_hooks(specs: &[TransitionHookSpec], path: &str) -> Result<Vec<TransitionHook>, ConfigError> {
+    specs
+        .iter()
+        .enumerate()
+        .map(|(idx, spec)| {
+            TransitionHook::parse(spec)
+                .map_err(|(field, message)| ConfigError::validation(format!("{}[{}].{}", path, idx, field), message))
+        })
+        .collect()
+}
+
+fn parse_time_windows(specs: &[TimeWindowSpec], path: &str) -> Result<Vec<TimeWindow>, ConfigError> {
+    specs
+        .iter()
+        .enumerate()
+        .map(|(idx, spec)| {
+            TimeWindow::parse(spec)
+                .map_err(|(field, message)| ConfigError::validation(format!("{}[{}].{}", path, idx, field), message))
+        })
+        .collect()
+}
+
+fn parse_profiles(specs: &[ProfileSpec], path: &str) -> Result<Vec<PolicyProfile>, ConfigError> {
+    let mut profiles = Vec::with_capacity(specs.len());
+    for (idx, spec) in specs.iter().enumerate() {
+        let field = |name: &str| format!("{}[{}].{}", path, idx, name);
+        if spec.name.trim().is_empty() {
+            return Err(ConfigError::validation(field("name"), "profile name cannot be empty"));
+        }
+        let window = TimeWindow::parse(&spec.window)
+            .map_err(|(name, message)| ConfigError::validation(field(name), message))?;
+        if spec.normal_secs == Some(0) {
+            return Err(ConfigError::validation(
+                field("secs-for-normal-loop"),
+                "normal interval must be greater than zero seconds",
+            ));
+        }
+        if spec.emergency_secs == Some(0) {
+            return Err(ConfigError::validation(
+                field("secs-for-emergency-loop"),
+                "emergency interval must be greater than zero seconds",
+            ));
+        }
+        let emergency_retries = match spec.emergency_retries {
+            Some(value) => Some(NonZeroU32::new(value).ok_or_else(|| {
+                ConfigError::validation(field("times-for-emergency-loop"), "emergency retry attempts must be at least 1")
+            })?),
+            None => None,
+        };
+        profiles.push(PolicyProfile {
+            name: spec.name.trim().to_string(),
+            window,
+            normal_interval: spec.normal_secs.map(Duration::from_secs),
+            emergency_interval: spec.emergency_secs.map(Duration::from_secs),
+            emergency_retries,
+            strict: spec.strict,
+        });
+    }
+    Ok(profiles)
+}
+
 #[derive(Debug, Clone)]
 struct FieldValue<T> {
     value: T,
@@ -331,12 +1292,123 @@ impl OutputInfo for MonitorConfig {
         println!("{} Effective configuration", "[CONFIG]".bold());
         println!("  targets     : {}", self.targets.join(", "));
         println!("  strict      : {}", self.strict);
+        if !self.dependencies.is_empty() {
+            let deps: Vec<String> =
+                self.dependencies.iter().map(|(child, parent)| format!("{} -> {}", child, parent)).collect();
+            println!("  depends on  : {}", deps.join(", "));
+        }
         println!("  normal      : {}s", self.normal_interval_secs());
+        match self.max_outage {
+            Some(max) => println!(
+                "  emergency   : {}s, act after {}s continuous outage",
+                self.emergency_interval_secs(),
+                max.as_secs()
+            ),
+            None => println!(
+                "  emergency   : {}s x{}",
+                self.emergency_interval_secs(),
+                self.emergency_retry_attempts()
+            ),
+        }
+        let actions: Vec<String> = self.actions.iter().map(|action| action.to_string()).collect();
+        match self.dry_run {
+            DryRun::Off => println!("  actions     : {}", actions.join(" -> ")),
+            mode => println!("  actions     : {} (dry run, then {})", actions.join(" -> "), mode.as_str()),
+        }
+        for (idx, stage) in self.escalation.iter().enumerate() {
+            let actions: Vec<String> = stage.actions.iter().map(|action| action.to_string()).collect();
+            println!(
+                "  stage {:<6}: {}, {}s x{} -> {}",
+                idx + 1,
+                stage.name,
+                stage.emergency_interval.as_secs(),
+                stage.emergency_retries,
+                actions.join(" -> ")
+            );
+        }
+        for step in &self.remediation {
+            println!("  remediate   : {} (settle {}s)", step.name, step.settle.as_secs());
+        }
+        if let Some(limit) = self.action_limit {
+            println!(
+                "  limit       : {} power action(s) per {}s, then log-only",
+                limit,
+                self.action_limit_window.as_secs()
+            );
+        }
         println!(
-            "  emergency   : {}s x{}",
-            self.emergency_interval_secs(),
-            self.emergency_retry_attempts()
+            "  thresholds  : fail {} / recover {}",
+            self.fail_threshold_cycles(),
+            self.recover_threshold_cycles()
         );
+        if self.policy == DecisionPolicy::Availability {
+            println!(
+                "  policy      : availability >= {}% of {} over {}s",
+                self.availability_threshold,
+                self.availability_basis.as_str(),
+                self.availability_window.as_secs()
+            );
+        }
+        if !self.flap_window.is_zero() {
+            println!(
+                "  flapping    : {} transitions / {}s, action {}",
+                self.flap_threshold,
+                self.flap_window.as_secs(),
+                self.flap_action.as_str()
+            );
+        }
+        match self.jitter {
+            JitterSpread::Percent(pct) => println!("  jitter      : +/-{}%", pct),
+            JitterSpread::Fixed(spread) => println!("  jitter      : +/-{}s", spread.as_secs()),
+            JitterSpread::None => {}
+        }
+        if !self.initial_offset.is_zero() {
+            println!("  offset      : up to {}s before the first check", self.initial_offset.as_secs());
+        }
+        for hook in &self.pre_action_hooks {
+            let veto = if hook.veto { ", vetoes on failure" } else { "" };
+            println!("  hook        : {} ({}s{})", hook.command, hook.timeout.as_secs(), veto);
+        }
+        for hook in &self.transition_hooks {
+            let json = if hook.json { ", JSON on stdin" } else { "" };
+            println!("  on {:<9}: {} ({}s{})", hook.event.as_str(), hook.command, hook.timeout.as_secs(), json);
+        }
+        for hook in &self.webhooks {
+            println!("  webhook     : {} ({}s, {} retries)", hook.url, hook.timeout.as_secs(), hook.retries);
+        }
+        if let Some(queue) = &self.webhook_queue {
+            println!("  queue       : {}", queue.display());
+        }
+        if let Some(smtp) = &self.smtp {
+            let recipients: Vec<String> = smtp.to.iter().map(|to| to.to_string()).collect();
+            println!(
+                "  smtp        : {}:{}{} -> {}",
+                smtp.server,
+                smtp.port,
+                if smtp.starttls { " (starttls)" } else { "" },
+                recipients.join(", ")
+            );
+        }
+        if let Some(mqtt) = &self.mqtt {
+            println!("  mqtt        : {}:{} ({}/...)", mqtt.host, mqtt.port, mqtt.prefix);
+        }
+        if !self.templates.overrides().is_empty() {
+            let names: Vec<&str> = self.templates.overrides().keys().map(String::as_str).collect();
+            println!("  templates   : {}", names.join(", "));
+        }
+        if let Some(countdown) = self.countdown {
+            println!("  countdown   : {}s warning before actions run", countdown.as_secs());
+        }
+        if let Some(grace) = self.startup_grace {
+            println!("  grace       : {}s from {}", grace.as_secs(), self.grace_clock.as_str());
+        }
+        for profile in &self.profiles {
+            println!("  profile     : {} ({})", profile.name, profile.window);
+        }
+        if !self.maintenance_windows.is_empty() {
+            let windows: Vec<String> = self.maintenance_windows.iter().map(|w| w.to_string()).collect();
+            println!("  maintenance : {}", windows.join("; "));
+        }
         println!("  verbose     : {}", self.verbose);
         println!("  quiet       : {}", self.quiet);
         println!("  status-only : {}", self.status_only);
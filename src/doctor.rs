@@ -0,0 +1,224 @@
+use std::fs::{self, OpenOptions};
+use std::net::{IpAddr, ToSocketAddrs};
+use std::path::Path;
+use colored::Colorize;
+
+use crate::cli::{Cli, Command};
+use crate::config::{build_monitor_config, MonitorConfig, GATEWAY_TARGET};
+use crate::runtime::METRICS_FILE;
+use crate::system::System;
+
+/// Exit code when every check passed
+pub const EXIT_PASS: i32 = 0;
+/// Exit code when some checks warned but none failed
+pub const EXIT_WARN: i32 = 1;
+/// Exit code when at least one check failed
+pub const EXIT_FAIL: i32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl CheckStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CheckStatus::Pass => "PASS",
+            CheckStatus::Warn => "WARN",
+            CheckStatus::Fail => "FAIL",
+        }
+    }
+}
+
+/// Outcome of one environment check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl CheckResult {
+    fn new(name: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self { name, status, detail: detail.into() }
+    }
+}
+
+/// Runs every check against the configuration `cli` resolves to.
+pub fn run_checks<S: System>(cli: &Cli, system: &S) -> Vec<CheckResult> {
+    let mut cli = cli.clone();
+    if let Some(Command::Doctor { targets }) = &cli.command {
+        if !targets.is_empty() {
+            cli.targets = targets.clone();
+        }
+    }
+    let config = build_monitor_config(&cli);
+    let mut results = vec![match &config {
+        Ok(cfg) => CheckResult::new("configuration", CheckStatus::Pass, format!("valid, {} target(s)", cfg.targets.len())),
+        Err(err) => CheckResult::new("configuration", CheckStatus::Fail, err.to_string()),
+    }];
+    results.push(check_ping_binary(system));
+    results.push(check_ping_socket());
+    results.push(match &config {
+        Ok(cfg) => check_dns(cfg, system),
+        Err(_) => CheckResult::new("dns", CheckStatus::Warn, "skipped, the configuration does not validate"),
+    });
+    results.push(check_shutdown_privilege(system));
+    results.push(check_metrics_file(Path::new(METRICS_FILE)));
+    results
+}
+
+/// Worst status across all results, as a process exit code.
+pub fn exit_code(results: &[CheckResult]) -> i32 {
+    match results.iter().map(|result| result.status).max() {
+        Some(CheckStatus::Fail) => EXIT_FAIL,
+        Some(CheckStatus::Warn) => EXIT_WARN,
+        _ => EXIT_PASS,
+    }
+}
+
+/// Prints the checks and returns the exit code for `pingdown doctor`.
+pub fn run<S: System>(cli: &Cli, system: &S) -> i32 {
+    println!("{} Checking the environment...", "[DOCTOR]".bold());
+    let results = run_checks(cli, system);
+    for result in &results {
+        let label = match result.status {
+            CheckStatus::Pass => result.status.as_str().bold().green(),
+            CheckStatus::Warn => result.status.as_str().bold().yellow(),
+            CheckStatus::Fail => result.status.as_str().bold().red(),
+        };
+        println!("  {}  {:<18} {}", label, result.name, result.detail);
+    }
+    let count = |status| results.iter().filter(|result| result.status == status).count();
+    println!(
+        "{} {} passed, {} warning(s), {} failure(s)",
+        "[DOCTOR]".bold(),
+        count(CheckStatus::Pass),
+        count(CheckStatus::Warn),
+        count(CheckStatus::Fail)
+    );
+    exit_code(&results)
+}
+
+fn check_ping_binary<S: System>(system: &S) -> CheckResult {
+    let command = system.build_ping_command("127.0.0.1");
+    match system.run_shell_command(&command, None) {
+        Err(err) => CheckResult::new("ping", CheckStatus::Fail, format!("cannot run '{}': {}", command, err)),
+        Ok(output) => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            if stdout.contains("TTL") || stdout.contains("ttl") {
+                CheckResult::new("ping", CheckStatus::Pass, format!("'{}' got a reply", command))
+            } else {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let reason = stderr.lines().next().unwrap_or("no reply from localhost");
+                CheckResult::new("ping", CheckStatus::Fail, format!("'{}' failed: {}", command, reason.trim()))
+            }
+        }
+    }
+}
+
+/// Unprivileged ICMP needs either ping sockets for our group, CAP_NET_RAW, or root.
+#[cfg(target_os = "linux")]
+fn check_ping_socket() -> CheckResult {
+    let status = fs::read_to_string("/proc/self/status").unwrap_or_default();
+    let field = |key: &str| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(key))
+            .map(|rest| rest.split_whitespace().map(str::to_string).collect::<Vec<_>>())
+            .unwrap_or_default()
+    };
+    // Effective ids are the second column of the Uid:/Gid: lines
+    let euid: Option<u32> = field("Uid:").get(1).and_then(|v| v.parse().ok());
+    let egid: Option<u32> = field("Gid:").get(1).and_then(|v| v.parse().ok());
+    if euid == Some(0) {
+        return CheckResult::new("icmp socket", CheckStatus::Pass, "running as root");
+    }
+    const CAP_NET_RAW: u32 = 13;
+    let cap_eff = field("CapEff:").first().and_then(|v| u64::from_str_radix(v, 16).ok()).unwrap_or(0);
+    if cap_eff & (1 << CAP_NET_RAW) != 0 {
+        return CheckResult::new("icmp socket", CheckStatus::Pass, "CAP_NET_RAW is effective");
+    }
+    let range = fs::read_to_string("/proc/sys/net/ipv4/ping_group_range").unwrap_or_default();
+    let bounds: Vec<u32> = range.split_whitespace().filter_map(|v| v.parse().ok()).collect();
+    if let (Some(gid), [low, high]) = (egid, bounds.as_slice()) {
+        if (*low..=*high).contains(&gid) {
+            return CheckResult::new("icmp socket", CheckStatus::Pass, format!("ping sockets allowed for gid {}", gid));
+        }
+    }
+    CheckResult::new(
+        "icmp socket",
+        CheckStatus::Warn,
+        "no CAP_NET_RAW and ping sockets are disabled for this group; ping must be setuid or carry file capabilities",
+    )
+}
+
+#[cfg(not(target_os = "linux"))]
+fn check_ping_socket() -> CheckResult {
+    CheckResult::new("icmp socket", CheckStatus::Pass, "handled by the system ping on this platform")
+}
+
+fn check_dns<S: System>(cfg: &MonitorConfig, system: &S) -> CheckResult {
+    let mut hosts: Vec<&str> = cfg.targets.iter().chain(cfg.dependencies.values()).map(|t| target_host(t)).collect();
+    hosts.sort_unstable();
+    hosts.dedup();
+    let mut resolved = 0;
+    let mut failed = Vec::new();
+    for host in hosts {
+        if host == GATEWAY_TARGET {
+            match system.default_gateway() {
+                Some(_) => resolved += 1,
+                None => failed.push(format!("{} (no default route)", GATEWAY_TARGET)),
+            }
+        } else if host.parse::<IpAddr>().is_err() {
+            match (host, 0).to_socket_addrs().map(|mut addrs| addrs.next().is_some()) {
+                Ok(true) => resolved += 1,
+                _ => failed.push(host.to_string()),
+            }
+        }
+    }
+    match (failed.is_empty(), resolved) {
+        (true, 0) => CheckResult::new("dns", CheckStatus::Pass, "no hostnames to resolve"),
+        (true, n) => CheckResult::new("dns", CheckStatus::Pass, format!("{} name(s) resolved", n)),
+        (false, _) => CheckResult::new("dns", CheckStatus::Fail, format!("cannot resolve {}", failed.join(", "))),
+    }
+}
+
+/// Host part of a target that may carry a scheme, credentials, port or path.
+fn target_host(target: &str) -> &str {
+    let rest = target.split_once("://").map_or(target, |(_, rest)| rest);
+    let rest = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    let rest = rest.rsplit_once('@').map_or(rest, |(_, host)| host);
+    if let Some(bracketed) = rest.strip_prefix('[') {
+        return bracketed.split(']').next().unwrap_or(bracketed);
+    }
+    rest.split(':').next().unwrap_or(rest)
+}
+
+fn check_shutdown_privilege<S: System>(system: &S) -> CheckResult {
+    match system.shutdown_privilege() {
+        Some(true) => CheckResult::new("shutdown", CheckStatus::Pass, "process may power off the machine"),
+        Some(false) => CheckResult::new(
+            "shutdown",
+            CheckStatus::Fail,
+            "not running with administrator/root rights; the emergency shutdown will fail",
+        ),
+        None => CheckResult::new("shutdown", CheckStatus::Warn, "cannot tell whether shutdown is permitted"),
+    }
+}
+
+/// Opens the metrics file for appending, removing it again when the check created it.
+fn check_metrics_file(path: &Path) -> CheckResult {
+    let existed = path.exists();
+    match OpenOptions::new().append(true).create(true).open(path) {
+        Ok(_) => {
+            if !existed {
+                let _ = fs::remove_file(path);
+            }
+            CheckResult::new("metrics file", CheckStatus::Pass, format!("'{}' is writable", path.display()))
+        }
+        Err(err) => CheckResult::new("metrics file", CheckStatus::Fail, format!("cannot write '{}': {}", path.display(), err)),
+    }
+}
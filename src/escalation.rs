@@ -0,0 +1,29 @@
+use std::num::NonZeroU32;
+use std::time::Duration;
+use serde::Deserialize;
+
+use crate::action::{Action, ActionSpec};
+
+/// Raw escalation stage as written in the configuration file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct StageSpec {
+    pub name: Option<String>,
+    /// Seconds between checks while this stage runs
+    #[serde(alias = "secs-for-emergency-loop")]
+    pub interval: Option<u64>,
+    /// Failed checks before this stage's actions run
+    #[serde(alias = "times-for-emergency-loop")]
+    pub tries: Option<u32>,
+    pub actions: Vec<ActionSpec>,
+}
+
+/// One rung of the escalation ladder. Its pacing and actions replace the base emergency
+/// settings while it is active; unset values were filled from the base configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EscalationStage {
+    pub name: String,
+    pub emergency_interval: Duration,
+    pub emergency_retries: NonZeroU32,
+    pub actions: Vec<Action>,
+}
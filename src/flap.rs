@@ -0,0 +1,107 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use serde::Deserialize;
+
+use crate::config::MonitorConfig;
+
+/// What to do with the shutdown action while the link is flapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FlapAction {
+    /// Flapping is reported but the action fires as usual
+    #[default]
+    None,
+    /// Skip the action and restart the emergency retry countdown
+    Suppress,
+    /// Hold the action until the link stops flapping, then fire if still down
+    Defer,
+}
+
+impl FlapAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FlapAction::None => "none",
+            FlapAction::Suppress => "suppress",
+            FlapAction::Defer => "defer",
+        }
+    }
+}
+
+/// Result of feeding the detector a transition or a clock tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlapChange {
+    Started,
+    Cleared,
+    Unchanged,
+}
+
+/// Sliding-window detector for links that bounce between up and down.
+///
+/// The monitor is flapping once `threshold` state transitions fall inside `window`,
+/// and stops flapping once a whole window passes without any transition.
+/// A zero window or threshold disables detection.
+#[derive(Debug, Clone)]
+pub struct FlapDetector {
+    window: Duration,
+    threshold: u32,
+    transitions: VecDeque<Instant>,
+    flapping: bool,
+}
+
+impl FlapDetector {
+    pub fn new(window: Duration, threshold: u32) -> Self {
+        Self { window, threshold, transitions: VecDeque::new(), flapping: false }
+    }
+
+    pub fn from_config(cfg: &MonitorConfig) -> Self {
+        Self::new(cfg.flap_window, cfg.flap_threshold)
+    }
+
+    pub fn enabled(&self) -> bool {
+        !self.window.is_zero() && self.threshold > 0
+    }
+
+    pub fn is_flapping(&self) -> bool { self.flapping }
+
+    /// Number of transitions currently inside the window.
+    pub fn transitions(&self) -> usize { self.transitions.len() }
+
+    pub fn window_secs(&self) -> u64 { self.window.as_secs() }
+
+    /// Records a normal <-> emergency transition observed at `now`.
+    pub fn record_transition(&mut self, now: Instant) -> FlapChange {
+        if !self.enabled() {
+            return FlapChange::Unchanged;
+        }
+        self.transitions.push_back(now);
+        self.prune(now);
+        if !self.flapping && self.transitions.len() >= self.threshold as usize {
+            self.flapping = true;
+            return FlapChange::Started;
+        }
+        FlapChange::Unchanged
+    }
+
+    /// Expires old transitions; clears the flapping state once the window is empty.
+    pub fn refresh(&mut self, now: Instant) -> FlapChange {
+        if !self.enabled() {
+            return FlapChange::Unchanged;
+        }
+        self.prune(now);
+        if self.flapping && self.transitions.is_empty() {
+            self.flapping = false;
+            return FlapChange::Cleared;
+        }
+        FlapChange::Unchanged
+    }
+
+    fn prune(&mut self, now: Instant) {
+        while let Some(front) = self.transitions.front() {
+            if now.saturating_duration_since(*front) > self.window {
+                self.transitions.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+}
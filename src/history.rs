@@ -0,0 +1,106 @@
+use std::io;
+use std::num::NonZeroU32;
+use std::path::Path;
+use std::time::Duration;
+use chrono::Utc;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+use crate::action::{Action, ActionKind};
+use crate::config::MonitorConfig;
+use crate::runtime::STATE_FILE;
+use crate::system::System;
+
+/// A power action pingdown started, as stored in the state file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActionRecord {
+    /// Unix time in seconds; wall-clock time so the record stays meaningful across reboots
+    pub at: i64,
+    pub action: String,
+}
+
+/// Power actions started recently, persisted so a reboot loop is noticed after a reboot.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActionHistory {
+    pub power_actions: Vec<ActionRecord>,
+}
+
+impl ActionHistory {
+    /// Reads the state file. A missing file is an empty history; an unreadable one is reported
+    /// and treated as empty, so a damaged file can never keep the actions from running at all.
+    pub fn load<S: System>(system: &S) -> Self {
+        let contents = match system.read_file(Path::new(STATE_FILE)) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Self::default(),
+            Err(err) => {
+                println!("{} Could not read {}: {}; starting a new history.", "[LIMIT]".bold().yellow(), STATE_FILE, err);
+                return Self::default();
+            }
+        };
+        serde_json::from_str(&contents).unwrap_or_else(|err| {
+            println!("{} {} is damaged ({}); starting a new history.", "[LIMIT]".bold().yellow(), STATE_FILE, err);
+            Self::default()
+        })
+    }
+
+    pub fn save<S: System>(&self, system: &S) -> io::Result<()> {
+        let contents = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        system.write_file(Path::new(STATE_FILE), &contents)
+    }
+
+    /// Drops the records that fell out of `window`.
+    pub fn prune(&mut self, now: i64, window: Duration) {
+        let oldest = now.saturating_sub(window.as_secs() as i64);
+        self.power_actions.retain(|record| record.at > oldest);
+    }
+}
+
+/// Applies `action-limit` to a pipeline that is about to run and returns the actions to run.
+/// Power actions within the limit are recorded in the state file before they start, since a
+/// reboot may not leave time afterwards; the ones beyond it are swapped for log-only.
+pub fn limit_actions<S: System>(cfg: &MonitorConfig, system: &S) -> Vec<Action> {
+    let Some(limit) = cfg.action_limit else { return cfg.actions.clone() };
+    if !cfg.actions.iter().any(|action| action.kind.is_power()) {
+        return cfg.actions.clone();
+    }
+    let now = Utc::now().timestamp();
+    let mut history = ActionHistory::load(system);
+    history.prune(now, cfg.action_limit_window);
+    let mut skipped = Vec::new();
+    let actions = cfg
+        .actions
+        .iter()
+        .map(|action| {
+            if !action.kind.is_power() {
+                return action.clone();
+            }
+            if history.power_actions.len() >= limit.get() as usize {
+                skipped.push(action.kind.as_str());
+                return Action { kind: ActionKind::LogOnly, timeout: action.timeout };
+            }
+            history.power_actions.push(ActionRecord { at: now, action: action.kind.as_str().to_string() });
+            action.clone()
+        })
+        .collect();
+    if !skipped.is_empty() {
+        report_limit(limit, cfg.action_limit_window, &skipped);
+    }
+    // A dry run must not use up the real budget
+    if !cfg.dry_run.enabled() {
+        if let Err(err) = history.save(system) {
+            println!("{} Could not write {}: {}", "[LIMIT]".bold().red(), STATE_FILE, err);
+        }
+    }
+    actions
+}
+
+fn report_limit(limit: NonZeroU32, window: Duration, skipped: &[&str]) {
+    println!(
+        "{} Limit of {} power action(s) per {}s reached; falling back to log-only instead of: {}.",
+        "[LIMIT]".bold().red(),
+        limit,
+        window.as_secs(),
+        skipped.join(", ")
+    );
+}
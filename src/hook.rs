@@ -0,0 +1,300 @@
+use std::time::{Duration, Instant};
+use chrono::{Local, TimeDelta};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+use crate::action::ActionResult;
+use crate::config::MonitorConfig;
+use crate::state::MonitorState;
+use crate::system::System;
+
+pub const DEFAULT_HOOK_TIMEOUT_SECS: u64 = 30;
+
+/// Raw hook entry as written in the configuration file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HookSpec {
+    pub command: String,
+    /// Seconds before the hook is killed
+    #[serde(alias = "timeout-secs")]
+    pub timeout: Option<u64>,
+    /// Whether a non-zero exit stops the actions from running
+    pub veto: bool,
+}
+
+/// A shell command run before the emergency actions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hook {
+    pub command: String,
+    pub timeout: Duration,
+    pub veto: bool,
+}
+
+impl Hook {
+    pub fn new(command: impl Into<String>, veto: bool) -> Self {
+        Self { command: command.into(), timeout: Duration::from_secs(DEFAULT_HOOK_TIMEOUT_SECS), veto }
+    }
+
+    /// Validates a raw entry; the error names the offending sub-field.
+    pub fn parse(spec: &HookSpec) -> Result<Self, (&'static str, String)> {
+        let command = spec.command.trim();
+        if command.is_empty() {
+            return Err(("command", "hook command cannot be empty".to_string()));
+        }
+        let timeout = match spec.timeout {
+            Some(0) => return Err(("timeout", "hook timeout must be greater than zero seconds".to_string())),
+            Some(secs) => Duration::from_secs(secs),
+            None => Duration::from_secs(DEFAULT_HOOK_TIMEOUT_SECS),
+        };
+        Ok(Self { command: command.to_string(), timeout, veto: spec.veto })
+    }
+}
+
+/// Monitor transitions that can trigger a hook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    /// pingdown started monitoring
+    Startup,
+    /// Connectivity was lost and the emergency loop began
+    Emergency,
+    /// A check inside the emergency loop failed
+    Retry,
+    /// Connectivity came back after an emergency
+    Recovery,
+}
+
+impl HookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookEvent::Startup => "startup",
+            HookEvent::Emergency => "emergency",
+            HookEvent::Retry => "retry",
+            HookEvent::Recovery => "recovery",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "startup" => Some(HookEvent::Startup),
+            "emergency" => Some(HookEvent::Emergency),
+            "retry" => Some(HookEvent::Retry),
+            "recovery" => Some(HookEvent::Recovery),
+            _ => None,
+        }
+    }
+}
+
+/// Raw transition hook entry as written in the configuration file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TransitionHookSpec {
+    /// Event name: startup, emergency, retry or recovery
+    pub on: String,
+    pub command: String,
+    #[serde(alias = "timeout-secs")]
+    pub timeout: Option<u64>,
+    /// Also pass the context as a JSON object on stdin
+    pub json: bool,
+}
+
+impl TransitionHookSpec {
+    /// Reads the `--hook` form: `<event>:<shell command>`.
+    pub fn from_cli(value: &str) -> Self {
+        let (on, command) = value.split_once(':').unwrap_or((value, ""));
+        Self { on: on.trim().to_string(), command: command.to_string(), ..Self::default() }
+    }
+}
+
+/// A shell command run when the monitor changes state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransitionHook {
+    pub event: HookEvent,
+    pub command: String,
+    pub timeout: Duration,
+    pub json: bool,
+}
+
+impl TransitionHook {
+    pub fn new(event: HookEvent, command: impl Into<String>) -> Self {
+        Self { event, command: command.into(), timeout: Duration::from_secs(DEFAULT_HOOK_TIMEOUT_SECS), json: false }
+    }
+
+    /// Validates a raw entry; the error names the offending sub-field.
+    pub fn parse(spec: &TransitionHookSpec) -> Result<Self, (&'static str, String)> {
+        let event = HookEvent::from_name(spec.on.trim()).ok_or_else(|| {
+            ("on", format!("'{}' is not a hook event (use startup, emergency, retry or recovery)", spec.on.trim()))
+        })?;
+        let hook = Hook::parse(&HookSpec { command: spec.command.clone(), timeout: spec.timeout, veto: false })?;
+        Ok(Self { event, command: hook.command, timeout: hook.timeout, json: spec.json })
+    }
+}
+
+/// What transition hooks learn about the change, as `PINGDOWN_*` variables and optionally JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TransitionContext {
+    pub event: &'static str,
+    /// Local time of the transition, RFC 3339
+    pub time: String,
+    /// State the monitor moved to
+    pub state: &'static str,
+    pub targets: Vec<String>,
+    /// Targets that did not answer in the cycle that caused the transition
+    pub failing_targets: Vec<String>,
+    pub failed_cycles: u32,
+    pub retries_left: Option<u32>,
+    /// Length of the outage so far, or of the one that just ended
+    pub outage_secs: u64,
+}
+
+impl TransitionContext {
+    pub fn new(event: HookEvent, state: MonitorState, failing_targets: Vec<String>, cfg: &MonitorConfig) -> Self {
+        let (failed_cycles, retries_left, outage) = match state {
+            MonitorState::Emergency { failed, retries_left, since, .. } => (failed, Some(retries_left), since.elapsed()),
+            MonitorState::Suspect { failed, since } => (failed, None, since.elapsed()),
+            MonitorState::Countdown { failed, since, .. } | MonitorState::Acting { failed, since } => {
+                (failed, Some(0), since.elapsed())
+            }
+            MonitorState::Recovered { outage } => (0, None, outage),
+            MonitorState::Normal => (0, None, Duration::ZERO),
+        };
+        Self {
+            event: event.as_str(),
+            time: Local::now().to_rfc3339(),
+            state: state.name(),
+            targets: cfg.targets.clone(),
+            failing_targets,
+            failed_cycles,
+            retries_left,
+            outage_secs: outage.as_secs(),
+        }
+    }
+
+    pub fn env(&self) -> Vec<(String, String)> {
+        [
+            ("PINGDOWN_EVENT", self.event.to_string()),
+            ("PINGDOWN_TIME", self.time.clone()),
+            ("PINGDOWN_STATE", self.state.to_string()),
+            ("PINGDOWN_TARGETS", self.targets.join(",")),
+            ("PINGDOWN_FAILING_TARGETS", self.failing_targets.join(",")),
+            ("PINGDOWN_FAILED_CYCLES", self.failed_cycles.to_string()),
+            ("PINGDOWN_RETRIES_LEFT", self.retries_left.map(|n| n.to_string()).unwrap_or_default()),
+            ("PINGDOWN_OUTAGE_SECS", self.outage_secs.to_string()),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect()
+    }
+}
+
+/// Runs the hooks registered for `event` in order. Hooks run synchronously within
+/// their timeout, so long-running automation should detach itself.
+pub fn run_transition_hooks<S: System>(
+    cfg: &MonitorConfig,
+    event: HookEvent,
+    state: MonitorState,
+    failing_targets: &[String],
+    system: &S,
+) {
+    let mut hooks = cfg.transition_hooks.iter().filter(|hook| hook.event == event).peekable();
+    if hooks.peek().is_none() {
+        return;
+    }
+    let context = TransitionContext::new(event, state, failing_targets.to_vec(), cfg);
+    let env = context.env();
+    let json = serde_json::to_string(&context).unwrap_or_default();
+    for hook in hooks {
+        if cfg.dry_run.enabled() {
+            println!(
+                "{} would run {} hook: {} (timeout {}s)",
+                "[DRY-RUN]".bold().cyan(),
+                event.as_str(),
+                hook.command,
+                hook.timeout.as_secs()
+            );
+            continue;
+        }
+        let stdin = hook.json.then_some(json.as_str());
+        match system.run_hook(&hook.command, &env, stdin, hook.timeout) {
+            ActionResult::Ok | ActionResult::DryRun => {}
+            ActionResult::Failed(reason) => {
+                println!("{} {} hook '{}' failed: {}", "[HOOK]".bold().yellow(), event.as_str(), hook.command, reason);
+            }
+            ActionResult::TimedOut => println!(
+                "{} {} hook '{}' timed out after {}s",
+                "[HOOK]".bold().yellow(),
+                event.as_str(),
+                hook.command,
+                hook.timeout.as_secs()
+            ),
+        }
+    }
+}
+
+/// What pre-action hooks learn about the outage, passed as `PINGDOWN_*` environment variables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutageContext {
+    /// Targets that were down or unreachable in the last cycle
+    pub failing_targets: Vec<String>,
+    pub since: Instant,
+    /// Failed checks made in the emergency loop
+    pub retries_used: u32,
+    /// Which policy made the action due: `retries`, `max-outage` or `availability`
+    pub policy: &'static str,
+}
+
+impl OutageContext {
+    pub fn env(&self, cfg: &MonitorConfig) -> Vec<(String, String)> {
+        let outage = self.since.elapsed();
+        let started = Local::now() - TimeDelta::from_std(outage).unwrap_or_default();
+        let actions: Vec<String> = cfg.actions.iter().map(|action| action.to_string()).collect();
+        [
+            ("PINGDOWN_FAILING_TARGETS", self.failing_targets.join(",")),
+            ("PINGDOWN_OUTAGE_START", started.to_rfc3339()),
+            ("PINGDOWN_OUTAGE_SECS", outage.as_secs().to_string()),
+            ("PINGDOWN_RETRIES_USED", self.retries_used.to_string()),
+            ("PINGDOWN_POLICY", self.policy.to_string()),
+            ("PINGDOWN_ACTIONS", actions.join(",")),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect()
+    }
+}
+
+/// Runs every pre-action hook in order. Returns the veto reason when a hook marked
+/// `veto` exited non-zero; a hook that times out never vetoes, so a hung script
+/// cannot keep the machine up indefinitely.
+pub fn run_pre_action_hooks<S: System>(cfg: &MonitorConfig, context: &OutageContext, system: &S) -> Option<String> {
+    if cfg.pre_action_hooks.is_empty() {
+        return None;
+    }
+    let env = context.env(cfg);
+    for hook in &cfg.pre_action_hooks {
+        if cfg.dry_run.enabled() {
+            println!(
+                "{} would run hook: {} (timeout {}s)",
+                "[DRY-RUN]".bold().cyan(),
+                hook.command,
+                hook.timeout.as_secs()
+            );
+            continue;
+        }
+        println!("{} Running '{}'...", "[HOOK]".bold().cyan(), hook.command);
+        match system.run_hook(&hook.command, &env, None, hook.timeout) {
+            ActionResult::Ok | ActionResult::DryRun => {}
+            ActionResult::Failed(reason) if hook.veto => {
+                return Some(format!("'{}' failed: {}", hook.command, reason));
+            }
+            ActionResult::Failed(reason) => {
+                println!("{} '{}' failed: {}", "[HOOK]".bold().yellow(), hook.command, reason);
+            }
+            ActionResult::TimedOut => println!(
+                "{} '{}' timed out after {}s",
+                "[HOOK]".bold().yellow(),
+                hook.command,
+                hook.timeout.as_secs()
+            ),
+        }
+    }
+    None
+}
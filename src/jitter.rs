@@ -0,0 +1,74 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::MonitorConfig;
+
+/// How far a sleep may be moved from its configured interval, in either direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JitterSpread {
+    #[default]
+    None,
+    /// Up to this percentage of the interval
+    Percent(u8),
+    /// Up to a fixed amount
+    Fixed(Duration),
+}
+
+/// Randomises check intervals so many hosts sharing a config do not probe in lockstep.
+///
+/// Uses a small SplitMix64 generator: the same seed always yields the same sequence
+/// of delays, which keeps tests reproducible.
+#[derive(Debug, Clone)]
+pub struct Jitter {
+    spread: JitterSpread,
+    state: u64,
+}
+
+impl Jitter {
+    pub fn new(spread: JitterSpread, seed: u64) -> Self {
+        Self { spread, state: seed }
+    }
+
+    /// Seeds from `jitter_seed` when configured, otherwise from the clock and process id.
+    pub fn from_config(cfg: &MonitorConfig) -> Self {
+        let seed = cfg.jitter_seed.unwrap_or_else(|| {
+            let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0);
+            nanos ^ ((std::process::id() as u64) << 32)
+        });
+        Self::new(cfg.jitter, seed)
+    }
+
+    /// `base` moved by a uniformly random offset within the spread, never below one second.
+    pub fn apply(&mut self, base: Duration) -> Duration {
+        let spread = match self.spread {
+            JitterSpread::None => return base,
+            JitterSpread::Percent(pct) => base.mul_f64(pct as f64 / 100.0),
+            JitterSpread::Fixed(spread) => spread,
+        };
+        let span = u64::try_from(spread.as_millis()).unwrap_or(u64::MAX);
+        if span == 0 {
+            return base;
+        }
+        let offset = match span.checked_mul(2).and_then(|width| width.checked_add(1)) {
+            Some(width) => self.next_u64() % width,
+            None => self.next_u64(),
+        };
+        base.saturating_add(Duration::from_millis(offset)).saturating_sub(spread).max(Duration::from_secs(1))
+    }
+
+    /// Uniformly random delay in `[0, max]`, used for the initial offset.
+    pub fn offset(&mut self, max: Duration) -> Duration {
+        let span = u64::try_from(max.as_millis()).unwrap_or(u64::MAX);
+        if span == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_millis(self.next_u64() % span.saturating_add(1))
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
@@ -1,11 +1,28 @@
+pub mod action;
 pub mod app;
 pub mod cli;
 pub mod config;
+pub mod doctor;
+pub mod escalation;
+pub mod flap;
+pub mod history;
+pub mod hook;
+pub mod jitter;
+#[cfg(unix)]
+pub mod logind;
+pub mod mail;
 pub mod monitor;
+pub mod mqtt;
+pub mod notify;
 pub mod ping;
+pub mod remediation;
 pub mod runtime;
+pub mod schedule;
 pub mod signals;
+pub mod state;
 pub mod system;
+pub mod template;
+pub mod webhook;
 
 // Re-exports for the thin binary wrapper and potential external users
 pub use app::App;
@@ -21,3 +38,4 @@ pub use config::{
     DEFAULT_CONFIG_PATH,
     ENV_CONFIG_PATH,
 };
+pub use state::{transition, CycleInput, Hold, MonitorState, StatePolicy, Step};
@@ -0,0 +1,115 @@
+//! Power actions through systemd-logind over D-Bus.
+//!
+//! logind applies polkit and inhibitor locks itself, so its answer is final whenever
+//! it can be reached; the shell fallback chain is only used when it cannot. Calls never
+//! allow interactive authorization, and a polkit `challenge` counts as a refusal. The
+//! system bus honours `DBUS_SYSTEM_BUS_ADDRESS`, and [`power_action_on`] accepts any
+//! connection, so a stand-in service on a private bus can take logind's place.
+
+use std::io;
+use std::time::{Duration, Instant};
+
+use zbus::blocking::{connection, Connection};
+
+use crate::action::{ActionAttempt, ActionKind, ActionResult};
+
+const DESTINATION: &str = "org.freedesktop.login1";
+const OBJECT: &str = "/org/freedesktop/login1";
+const INTERFACE: &str = "org.freedesktop.login1.Manager";
+/// `SD_LOGIND_ROOT_CHECK_INHIBITORS`: make block inhibitors apply to root as well.
+/// `SD_LOGIND_INTERACTIVE` stays clear, so polkit never prompts.
+const ROOT_CHECK_INHIBITORS: u64 = 1;
+const UNKNOWN_METHOD: &str = "org.freedesktop.DBus.Error.UnknownMethod";
+
+/// logind Manager method for a power action.
+pub fn method(action: &ActionKind) -> Option<&'static str> {
+    match action {
+        ActionKind::Poweroff => Some("PowerOff"),
+        ActionKind::Reboot => Some("Reboot"),
+        ActionKind::Suspend => Some("Suspend"),
+        ActionKind::Hibernate => Some("Hibernate"),
+        ActionKind::RunCommand(_) | ActionKind::LogOnly => None,
+    }
+}
+
+/// The D-Bus call that performs `action`, as shown in dry runs.
+pub fn command_line(action: &ActionKind) -> Option<String> {
+    method(action).map(|method| call_text(&format!("{}WithFlags", method), &ROOT_CHECK_INHIBITORS.to_string()))
+}
+
+/// Runs `action` through logind on the system bus and returns its result with the calls
+/// made. Returns `None` when logind is not reachable, so the caller can fall back to the
+/// shell commands.
+pub fn power_action(action: &ActionKind, timeout: Duration) -> Option<(ActionResult, Vec<ActionAttempt>)> {
+    method(action)?;
+    let connection = connection::Builder::system().ok()?.method_timeout(timeout).build().ok()?;
+    power_action_on(&connection, action)
+}
+
+/// [`power_action`] over an existing connection; method calls time out as the connection
+/// was configured.
+pub fn power_action_on(connection: &Connection, action: &ActionKind) -> Option<(ActionResult, Vec<ActionAttempt>)> {
+    let method = method(action)?;
+    let can = format!("Can{}", method);
+    let started = Instant::now();
+    let answer: String = connection
+        .call_method(Some(DESTINATION), OBJECT, Some(INTERFACE), can.as_str(), &())
+        .ok()?
+        .body()
+        .deserialize()
+        .ok()?;
+    let refusal = match answer.as_str() {
+        "yes" => None,
+        "challenge" => Some(format!("logind: {} is 'challenge', polkit wants interactive authentication", can)),
+        "no" => Some(format!("logind: {} is 'no', polkit does not allow it", can)),
+        "na" => Some(format!("logind: {} is not available on this system", method)),
+        _ => return None,
+    };
+    if let Some(reason) = refusal {
+        let attempt = ActionAttempt {
+            command: call_text(&can, ""),
+            result: ActionResult::Failed(reason.clone()),
+            status: Some(format!("replied '{}'", answer)),
+            stderr: String::new(),
+            elapsed: started.elapsed(),
+        };
+        return Some((ActionResult::Failed(reason), vec![attempt]));
+    }
+
+    let with_flags = format!("{}WithFlags", method);
+    let mut attempts = vec![call(connection, &with_flags, &ROOT_CHECK_INHIBITORS, &ROOT_CHECK_INHIBITORS.to_string())];
+    // Older systemd has no *WithFlags methods; inhibitors still bind non-root callers there
+    if attempts[0].status.as_deref() == Some(UNKNOWN_METHOD) {
+        attempts.push(call(connection, method, &false, "false"));
+    }
+    let result = match attempts.last().map(|attempt| attempt.result.clone()) {
+        Some(ActionResult::Failed(reason)) => ActionResult::Failed(format!("logind: {}", reason)),
+        Some(other) => other,
+        None => ActionResult::Ok,
+    };
+    Some((result, attempts))
+}
+
+/// Calls a Manager method and records its reply; a D-Bus error's name becomes the status.
+fn call<B>(connection: &Connection, method: &str, body: &B, shown: &str) -> ActionAttempt
+where
+    B: serde::Serialize + zbus::zvariant::DynamicType,
+{
+    let started = Instant::now();
+    let (result, status, stderr) = match connection.call_method(Some(DESTINATION), OBJECT, Some(INTERFACE), method, body) {
+        Ok(_) => (ActionResult::Ok, Some("method return".to_string()), String::new()),
+        Err(zbus::Error::InputOutput(err)) if err.kind() == io::ErrorKind::TimedOut => {
+            (ActionResult::TimedOut, None, String::new())
+        }
+        Err(zbus::Error::MethodError(name, detail, _)) => {
+            let reason = detail.clone().unwrap_or_else(|| name.to_string());
+            (ActionResult::Failed(reason), Some(name.to_string()), detail.unwrap_or_default())
+        }
+        Err(err) => (ActionResult::Failed(err.to_string()), None, String::new()),
+    };
+    ActionAttempt { command: call_text(method, shown), result, status, stderr, elapsed: started.elapsed() }
+}
+
+fn call_text(method: &str, args: &str) -> String {
+    format!("{}.{}({})", INTERFACE, method, args)
+}
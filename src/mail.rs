@@ -0,0 +1,130 @@
+use std::time::Duration;
+use colored::Colorize;
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use minijinja::{context, Value};
+use serde::Deserialize;
+
+use crate::notify::{split_host_port, Notification, NotifyEvent};
+use crate::template::Templates;
+
+pub const DEFAULT_SMTP_PORT: u16 = 25;
+/// Submission port, used by default together with STARTTLS
+pub const DEFAULT_STARTTLS_PORT: u16 = 587;
+pub const DEFAULT_SMTP_TIMEOUT_SECS: u64 = 30;
+
+/// Raw SMTP settings as written in the configuration file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SmtpSpec {
+    /// Host name or address of the relay, optionally with `:port` (`[addr]:port` for IPv6)
+    pub server: String,
+    pub port: Option<u16>,
+    pub starttls: bool,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+    #[serde(alias = "timeout-secs")]
+    pub timeout: Option<u64>,
+}
+
+/// A mail relay that alerts are sent through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Smtp {
+    pub server: String,
+    pub port: u16,
+    /// Upgrade the connection with STARTTLS and refuse to send without it
+    pub starttls: bool,
+    pub credentials: Option<(String, String)>,
+    pub from: Mailbox,
+    pub to: Vec<Mailbox>,
+    pub timeout: Duration,
+}
+
+impl Smtp {
+    /// Validates the raw settings; the error names the offending sub-field.
+    pub fn parse(spec: &SmtpSpec) -> Result<Self, (&'static str, String)> {
+        let server = spec.server.trim();
+        let Some((server, port)) = split_host_port(server) else {
+            return Err(("server", format!("invalid port in '{}'", server)));
+        };
+        if server.is_empty() {
+            return Err(("server", "SMTP server cannot be empty".to_string()));
+        }
+        let default_port = if spec.starttls { DEFAULT_STARTTLS_PORT } else { DEFAULT_SMTP_PORT };
+        let port = match spec.port.or(port) {
+            Some(0) => return Err(("port", "SMTP port must be greater than zero".to_string())),
+            Some(port) => port,
+            None => default_port,
+        };
+        let from = spec
+            .from
+            .trim()
+            .parse::<Mailbox>()
+            .map_err(|err| ("from", format!("'{}' is not a valid address: {}", spec.from, err)))?;
+        if spec.to.is_empty() {
+            return Err(("to", "at least one recipient is required".to_string()));
+        }
+        let to = spec
+            .to
+            .iter()
+            .map(|addr| {
+                addr.trim().parse::<Mailbox>().map_err(|err| ("to", format!("'{}' is not a valid address: {}", addr, err)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let credentials = match (&spec.username, &spec.password) {
+            (Some(user), Some(password)) => Some((user.clone(), password.clone())),
+            (Some(_), None) => return Err(("password", "a password is required with a username".to_string())),
+            (None, Some(_)) => return Err(("username", "a username is required with a password".to_string())),
+            (None, None) => None,
+        };
+        let timeout = match spec.timeout {
+            Some(0) => return Err(("timeout", "SMTP timeout must be greater than zero seconds".to_string())),
+            Some(secs) => Duration::from_secs(secs),
+            None => Duration::from_secs(DEFAULT_SMTP_TIMEOUT_SECS),
+        };
+        Ok(Self { server: server.to_string(), port, starttls: spec.starttls, credentials, from, to, timeout })
+    }
+
+    /// Sends one plain-text message to every recipient.
+    pub fn send(&self, subject: &str, body: &str) -> Result<(), String> {
+        let builder = if self.starttls {
+            SmtpTransport::starttls_relay(&self.server).map_err(|err| err.to_string())?
+        } else {
+            SmtpTransport::builder_dangerous(&self.server)
+        };
+        let mut builder = builder.port(self.port).timeout(Some(self.timeout));
+        if let Some((user, password)) = &self.credentials {
+            builder = builder.credentials(Credentials::new(user.clone(), password.clone()));
+        }
+        let mut message = Message::builder().from(self.from.clone()).subject(subject).header(ContentType::TEXT_PLAIN);
+        for to in &self.to {
+            message = message.to(to.clone());
+        }
+        let message = message.body(body.to_string()).map_err(|err| err.to_string())?;
+        builder.build().send(&message).map(|_| ()).map_err(|err| err.to_string())
+    }
+}
+
+/// Subject and body of the alert for `notification`, or `None` for events that are not mailed.
+pub fn render(notification: &Notification, templates: &Templates) -> Option<(String, String)> {
+    if notification.event == NotifyEvent::Startup {
+        return None;
+    }
+    let mut root_causes: Vec<&str> =
+        notification.targets.iter().filter_map(|target| target.root_cause.as_deref()).collect();
+    root_causes.dedup();
+    let ctx = context! { root_causes => root_causes, ..Value::from_serialize(notification) };
+    Some((templates.render("mail-subject", ctx.clone()), templates.render("mail-body", ctx)))
+}
+
+/// Mails the alert for `notification`, reporting a failure without retrying.
+pub fn send_alert(smtp: &Smtp, notification: &Notification, templates: &Templates) {
+    let Some((subject, body)) = render(notification, templates) else { return };
+    if let Err(err) = smtp.send(&subject, &body) {
+        println!("{} Sending '{}' through {}:{} failed: {}", "[MAIL]".bold().red(), subject, smtp.server, smtp.port, err);
+    }
+}
@@ -1,101 +1,750 @@
 use std::io::{self, Write};
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use chrono::Local;
 use colored::Colorize;
+use minijinja::{context, Value};
 
-use crate::config::MonitorConfig;
-use crate::ping::check_status;
-use crate::runtime::{add_one, MetricEvent, Metrics};
-use crate::system::{error, System};
+use crate::action::{run_actions, ActionKind, ActionOutcome, ActionResult, DryRun, EXIT_DRY_RUN};
+use crate::config::{DecisionPolicy, GraceClock, MonitorConfig};
+use crate::flap::{FlapAction, FlapChange, FlapDetector};
+use crate::history::limit_actions;
+use crate::hook::{run_pre_action_hooks, run_transition_hooks, HookEvent, OutageContext};
+use crate::jitter::Jitter;
+use crate::mqtt::MqttPublisher;
+use crate::notify::{host_name, target_results, Notifier, NotifyEvent};
+use crate::ping::{check_targets, CycleReport};
+use crate::remediation::run_remediation;
+use crate::schedule::{active_profile, TimeWindow};
+use crate::runtime::{add_one, process_started, track_escalations, CycleHistory, CycleSample, MetricEvent, Metrics, METRICS_FILE};
+use crate::state::{transition, CycleInput, Hold, MonitorState, StatePolicy};
+use crate::system::System;
 
 /// Continuously monitors connectivity in regular intervals
 pub fn normal_loop<S: System>(info: MonitorConfig, metrics: Arc<Mutex<Metrics>>, system: &S) {
-    let secs = info.normal_interval_secs();
-    println!("{} {}sec loop...", "[NORMAL]".bold().green(), secs);
-    for i in 0.. {
-        let (status, succeeds, failures) = check_status(&info, &metrics, system);
-        if !status {
-            emergency_loop(&info, &metrics, system);
-            continue;
-        }
-        add_one(&metrics, MetricEvent::NormalLoopTimes);
-        if i >= 1 && info.verbose > 0 {
-            println!("{} Normal loop {}", "[NORMAL]".bold().green(), i);
+    let mut driver = Driver::new(info, &metrics, system, true);
+    let ctx = context! {
+        interval => driver.info.normal_interval_secs(),
+        threshold => driver.info.availability_threshold,
+        window => driver.history.as_ref().map(CycleHistory::window_secs),
+        ..driver.event_context(MonitorState::Normal)
+    };
+    println!("{}", driver.info.templates.render("start", ctx));
+    run_transition_hooks(&driver.info, HookEvent::Startup, MonitorState::Normal, &[], system);
+    driver.notifier.notify(NotifyEvent::Startup, MonitorState::Normal, &driver.info, None);
+    initial_offset(&driver.info, &mut driver.jitter, "[NORMAL]");
+    let mut state = MonitorState::Normal;
+    loop {
+        let (next, delay) = driver.cycle(state);
+        driver.sleep(next, delay);
+        state = next;
+    }
+}
+
+/// Thin driver around [`transition`]: probes the targets, feeds the result to the
+/// state machine, reports what changed and runs the emergency action once it is due.
+///
+/// Everything the pure state machine cannot know — maintenance windows, the startup
+/// grace period, flapping — is folded into the [`Hold`] passed with each cycle, and under
+/// the availability policy the sliding window is kept here and passed in as `availability_low`.
+struct Driver<'a, S: System> {
+    info: MonitorConfig,
+    profiles: Option<ProfileSwitch>,
+    metrics: &'a Arc<Mutex<Metrics>>,
+    system: &'a S,
+    flap: FlapDetector,
+    jitter: Jitter,
+    deferred: bool,
+    normal_cycles: u64,
+    /// Parents that took dependent targets down in the last cycle, for the emergency output
+    root_cause: String,
+    /// Targets that failed in the last cycle, for the pre-action hooks
+    failing: Vec<String>,
+    /// Failed emergency checks made before the action fell due
+    retries_used: u32,
+    /// Index of the active escalation stage
+    stage: usize,
+    /// Outcome of the last cycle, for notifications
+    report: CycleReport,
+    notifier: Notifier,
+    mqtt: Option<MqttPublisher>,
+    host: String,
+    /// Recent cycles, kept under the availability policy only
+    history: Option<CycleHistory>,
+    /// Availability over `history` after the last cycle
+    availability: f64,
+}
+
+impl<'a, S: System> Driver<'a, S> {
+    fn new(info: MonitorConfig, metrics: &'a Arc<Mutex<Metrics>>, system: &'a S, follow_profiles: bool) -> Self {
+        let info = match info.escalation.first() {
+            Some(stage) => {
+                track_escalations(metrics);
+                info.with_stage(stage)
+            }
+            None => info,
+        };
+        let notifier = Notifier::start(&info);
+        let mqtt = info.mqtt.as_ref().map(MqttPublisher::start);
+        let history = (info.policy == DecisionPolicy::Availability)
+            .then(|| CycleHistory::new(info.availability_window, Instant::now()));
+        Self {
+            profiles: follow_profiles.then(|| ProfileSwitch::new(info.clone())),
+            flap: FlapDetector::from_config(&info),
+            jitter: Jitter::from_config(&info),
+            info,
+            metrics,
+            system,
+            deferred: false,
+            normal_cycles: 0,
+            root_cause: String::new(),
+            failing: Vec::new(),
+            retries_used: 0,
+            stage: 0,
+            report: CycleReport::default(),
+            notifier,
+            mqtt,
+            host: host_name(),
+            history,
+            availability: 100.0,
+        }
+    }
+
+    /// Runs one probing cycle from `state`; returns the next state and the delay before the following cycle.
+    fn cycle(&mut self, state: MonitorState) -> (MonitorState, Duration) {
+        // Profiles switch between outages only; a running countdown keeps its values
+        if !state.is_emergency() {
+            if let Some(effective) = self.profiles.as_mut().and_then(ProfileSwitch::poll) {
+                self.info = effective;
+                self.set_stage(0);
+            }
+        }
+        self.report_countdown(state);
+        let report = check_targets(&self.info, self.metrics, self.system);
+        let (mut ok, mut succeeds, mut failures) = (report.ok, report.succeeds, report.failures);
+        self.root_cause = root_cause(&report);
+        self.failing = report.failing_targets();
+        self.report = report;
+        let now = Instant::now();
+        let availability_low = self.record_availability(ok, succeeds, failures, now);
+        report_flap_change(self.flap.refresh(now), &self.flap);
+        let suppression = if ok {
+            None
+        } else {
+            action_suppression(&self.info, self.system).map(|s| (s.label(), s.reason()))
+        };
+        let hold = match (&suppression, self.flap.is_flapping(), self.info.flap_action) {
+            (Some(_), _, _) | (None, true, FlapAction::Suppress) => Some(Hold::Restart),
+            (None, true, FlapAction::Defer) => Some(Hold::Wait),
+            _ => None,
+        };
+        // Availability is judged afresh every cycle, so a held action simply stays due
+        let hold = match self.info.policy {
+            DecisionPolicy::Availability => hold.map(|_| Hold::Wait),
+            DecisionPolicy::Consecutive => hold,
+        };
+        let policy = StatePolicy::from(&self.info);
+        let mut step = transition(state, CycleInput { ok, at: now, hold, availability_low }, &policy);
+        // Remediation gets one chance per escalation, before any countdown or action
+        let due = matches!(step.state, MonitorState::Countdown { .. } | MonitorState::Acting { .. });
+        if due && !matches!(state, MonitorState::Countdown { .. }) && !self.info.remediation.is_empty() {
+            match run_remediation(&self.info, self.metrics, self.system) {
+                Some(fixed) => {
+                    (ok, succeeds, failures) = (true, fixed.succeeds, fixed.failures);
+                    self.root_cause.clear();
+                    self.failing.clear();
+                    self.report = CycleReport { ok, succeeds, failures, ..CycleReport::default() };
+                    self.restart_window();
+                    let input = CycleInput { ok, at: Instant::now(), hold: None, availability_low: false };
+                    step = transition(state, input, &policy);
+                }
+                None => println!(
+                    "{} All {} remediation step(s) failed; escalating.",
+                    "[REMEDIATE]".bold().red(),
+                    self.info.remediation.len()
+                ),
+            }
+        }
+        if step.state.is_emergency() && !state.is_emergency() {
+            report_flap_change(self.flap.record_transition(now), &self.flap);
+            self.report_entry(step.state);
+        }
+        match (step.held, suppression) {
+            (Some(Hold::Restart), Some((label, reason))) => {
+                println!("{} Shutdown suppressed {}; restarting retries.", label.bold().yellow(), reason);
+            }
+            (Some(Hold::Wait), Some((label, reason))) if !self.deferred => {
+                println!("{} Shutdown held back {}.", label.bold().yellow(), reason);
+            }
+            (Some(Hold::Restart), None) => {
+                println!("{} Shutdown suppressed while the link is flapping; restarting retries.", "[FLAPPING]".bold().yellow());
+            }
+            (Some(Hold::Wait), None) if !self.deferred => {
+                println!("{} Shutdown deferred until the link stops flapping.", "[FLAPPING]".bold().yellow());
+            }
+            _ => {}
+        }
+        self.deferred = step.held == Some(Hold::Wait);
+        match (state, step.state) {
+            (MonitorState::Countdown { .. }, _) => {}
+            (_, MonitorState::Countdown { failed, .. } | MonitorState::Acting { failed, .. }) => {
+                self.retries_used = match self.info.policy {
+                    DecisionPolicy::Consecutive => failed.saturating_sub(self.info.fail_threshold_cycles()),
+                    DecisionPolicy::Availability => 0,
+                };
+            }
+            _ => {}
+        }
+        match (state, step.state) {
+            (MonitorState::Countdown { .. }, MonitorState::Countdown { .. } | MonitorState::Acting { .. }) => {}
+            (MonitorState::Countdown { .. }, _) => self.cancel_countdown(ok),
+            (_, MonitorState::Countdown { since, deadline, .. }) => self.announce_countdown(since, deadline),
+            _ => {}
         }
+        let event = match (state.is_emergency(), step.state) {
+            (_, MonitorState::Recovered { .. }) => Some(HookEvent::Recovery),
+            (false, next) if next.is_emergency() => Some(HookEvent::Emergency),
+            (true, _) if !ok => Some(HookEvent::Retry),
+            _ => None,
+        };
+        if let Some(event) = event {
+            run_transition_hooks(&self.info, event, step.state, &self.failing, self.system);
+        }
+        let notify = match event {
+            Some(HookEvent::Emergency) => Some(NotifyEvent::Emergency),
+            Some(HookEvent::Recovery) => Some(NotifyEvent::Recovery),
+            _ => None,
+        };
+        if let Some(notify) = notify {
+            self.notifier.notify(notify, step.state, &self.info, Some(&self.report));
+        }
+        if ok {
+            self.notifier.connected();
+        }
+        if let Some(mqtt) = &self.mqtt {
+            mqtt.publish_cycle(step.state, &self.info.targets, &self.report);
+        }
+
+        let delay = match step.state {
+            MonitorState::Normal => {
+                let next = self.jitter.apply(self.info.normal_interval);
+                add_one(self.metrics, MetricEvent::NormalLoopTimes);
+                if self.normal_cycles >= 1 && self.info.verbose > 0 {
+                    println!("{} Normal loop {}", "[NORMAL]".bold().green(), self.normal_cycles);
+                }
+                self.normal_cycles += 1;
+                let base = self.line_context(step.state, succeeds, failures, next);
+                let line = if self.history.is_some() {
+                    self.availability_line(ok, base)
+                } else if self.flap.is_flapping() {
+                    let ctx = context! { transitions => self.flap.transitions(), flap_window => self.flap.window_secs(), ..base };
+                    self.info.templates.render("flapping", ctx)
+                } else {
+                    self.info.templates.render("normal", base)
+                };
+                println!("{}", line);
+                next
+            }
+            MonitorState::Suspect { failed, .. } => {
+                let next = self.jitter.apply(self.info.normal_interval);
+                let base = self.line_context(step.state, succeeds, failures, next);
+                let line = if self.history.is_some() {
+                    self.availability_line(ok, base)
+                } else {
+                    let ctx = context! { failed => failed, fail_threshold => self.info.fail_threshold_cycles(), ..base };
+                    self.info.templates.render("degraded", ctx)
+                };
+                println!("{}", line);
+                next
+            }
+            MonitorState::Emergency { failed, recovered, retries_left, countdown_from, .. } => {
+                let next = emergency_delay(&self.info, countdown_from, &mut self.jitter);
+                // The cycle that escalated was already reported by `report_entry`
+                if state.is_emergency() {
+                    let (color, label) = match (ok, self.flap.is_flapping()) {
+                        (true, _) => ("yellow", "RECOVERING"),
+                        (false, true) => ("red", "FLAPPING"),
+                        (false, false) => ("red", "DOWN"),
+                    };
+                    let base = self.line_context(step.state, succeeds, failures, next);
+                    let ctx = context! {
+                        color => color,
+                        label => label,
+                        failed => failed,
+                        recovered => recovered,
+                        recover_threshold => self.info.recover_threshold_cycles(),
+                        retries_left => retries_left,
+                        remaining => self.info.max_outage.map(|max| max.saturating_sub(countdown_from.elapsed()).as_secs()),
+                        ..base
+                    };
+                    println!("{}", self.info.templates.render("emergency", ctx));
+                    add_one(self.metrics, MetricEvent::EmergencyLoopTimes);
+                }
+                next
+            }
+            MonitorState::Countdown { failed, deadline, .. } => {
+                let left = deadline.saturating_duration_since(now);
+                let next = self.jitter.apply(self.info.emergency_interval).min(left).max(Duration::from_secs(1));
+                let base = self.line_context(step.state, succeeds, failures, next);
+                let ctx = context! { failed => failed, remaining => left.as_secs(), ..base };
+                println!("{}", self.info.templates.render("countdown", ctx));
+                add_one(self.metrics, MetricEvent::EmergencyLoopTimes);
+                next
+            }
+            MonitorState::Acting { since, .. } => {
+                if self.act(step.state, since) {
+                    self.escalate();
+                }
+                self.restart_window();
+                self.jitter.apply(self.info.emergency_interval)
+            }
+            MonitorState::Recovered { outage } => {
+                if self.stage > 0 {
+                    println!(
+                        "{} Recovered at stage {}/{}; back to stage 1.",
+                        "[ESCALATE]".bold().green(),
+                        self.stage + 1,
+                        self.info.escalation.len()
+                    );
+                    self.set_stage(0);
+                }
+                report_flap_change(self.flap.record_transition(now), &self.flap);
+                if !self.flap.is_flapping() {
+                    let ctx = context! {
+                        outage_secs => outage.as_secs(),
+                        interval => self.info.emergency_interval_secs(),
+                        ..self.event_context(step.state)
+                    };
+                    println!("{}", self.info.templates.render("recovery", ctx));
+                }
+                self.jitter.apply(self.info.normal_interval)
+            }
+        };
+        (step.state, delay)
+    }
+
+    fn sleep(&self, state: MonitorState, delay: Duration) {
+        let prefix = if state.is_emergency() { "[EMERGENCY]" } else { "[NORMAL]" };
+        sleep_with_progress(delay, self.info.progress, prefix);
+    }
+
+    fn report_entry(&self, state: MonitorState) {
+        // An outage can also enter with the action already due
+        let (failed, retries_left) = match state {
+            MonitorState::Emergency { failed, retries_left, .. } => (failed, retries_left),
+            MonitorState::Countdown { failed, .. } | MonitorState::Acting { failed, .. } => (failed, 0),
+            _ => return,
+        };
+        if self.flap.is_flapping() {
+            return;
+        }
+        let ctx = context! {
+            failed => failed,
+            interval => self.info.emergency_interval_secs(),
+            retries_left => retries_left,
+            max_outage => self.info.max_outage.map(|max| max.as_secs()),
+            availability => self.availability,
+            threshold => self.info.availability_threshold,
+            window => self.history.as_ref().map(CycleHistory::window_secs),
+            ..self.event_context(state)
+        };
+        println!("{}", self.info.templates.render("emergency-enter", ctx));
+    }
+
+    /// Records the cycle under the availability policy; true once availability over a full
+    /// window is below the threshold.
+    fn record_availability(&mut self, ok: bool, succeeds: u64, failures: u64, now: Instant) -> bool {
+        let Some(history) = self.history.as_mut() else {
+            return false;
+        };
+        history.record(CycleSample { at: now, ok, succeeds, failures });
+        self.availability = history.availability(self.info.availability_basis).unwrap_or(100.0);
+        history.covers_window(now) && self.availability < self.info.availability_threshold as f64
+    }
+
+    /// Judges the next full window afresh once the actions ran, rather than acting on every failed cycle.
+    fn restart_window(&mut self) {
+        if let Some(history) = self.history.as_mut() {
+            *history = CycleHistory::new(self.info.availability_window, Instant::now());
+        }
+    }
+
+    /// Status line of a normal or suspect cycle under the availability policy.
+    fn availability_line(&self, ok: bool, base: Value) -> String {
+        let (color, label) = if ok { ("green", "OK") } else { ("red", "DOWN") };
+        let ctx = context! {
+            color => color,
+            label => label,
+            availability => self.availability,
+            threshold => self.info.availability_threshold,
+            cycles => self.history.as_ref().map_or(0, CycleHistory::len),
+            ..base
+        };
+        self.info.templates.render("availability", ctx)
+    }
+
+    /// Variables shared by the console templates.
+    fn event_context(&self, state: MonitorState) -> Value {
+        let stage = self
+            .info
+            .escalation
+            .get(self.stage)
+            .map(|stage| format!("{}/{} {}", self.stage + 1, self.info.escalation.len(), stage.name));
+        context! {
+            host => &self.host,
+            time => Local::now().to_rfc3339(),
+            state => state.name(),
+            policy => self.policy_name(),
+            targets => target_results(&self.info.targets, Some(&self.report)),
+            failing_targets => &self.failing,
+            root_cause => &self.root_cause,
+            stage => stage,
+        }
+    }
+
+    /// Variables shared by the per-cycle console templates.
+    fn line_context(&self, state: MonitorState, succeeds: u64, failures: u64, next: Duration) -> Value {
+        context! { up => succeeds, down => failures, next => next.as_secs(), ..self.event_context(state) }
+    }
+
+    /// What decides that the actions are due: `retries`, `max-outage` or `availability`.
+    fn policy_name(&self) -> &'static str {
+        match (self.info.policy, self.info.max_outage) {
+            (DecisionPolicy::Availability, _) => "availability",
+            (DecisionPolicy::Consecutive, Some(_)) => "max-outage",
+            (DecisionPolicy::Consecutive, None) => "retries",
+        }
+    }
+
+    /// Applies escalation stage `idx` on top of the current configuration.
+    fn set_stage(&mut self, idx: usize) {
+        if let Some(stage) = self.info.escalation.get(idx) {
+            self.info = self.info.with_stage(stage);
+            self.stage = idx;
+        }
+    }
+
+    /// Moves up the ladder once a stage's actions ran; the last stage repeats while the outage lasts.
+    fn escalate(&mut self) {
+        let total = self.info.escalation.len();
+        if total == 0 {
+            return;
+        }
+        add_one(self.metrics, MetricEvent::StageEscalations);
+        if self.stage + 1 < total {
+            self.set_stage(self.stage + 1);
+            let stage = &self.info.escalation[self.stage];
+            println!(
+                "{} Moving to stage {}/{} '{}' | {}s x{}",
+                "[ESCALATE]".bold().red(),
+                self.stage + 1,
+                total,
+                stage.name,
+                stage.emergency_interval.as_secs(),
+                stage.emergency_retries
+            );
+        } else {
+            println!(
+                "{} Final stage '{}' done; repeating it while the outage lasts.",
+                "[ESCALATE]".bold().red(),
+                self.info.escalation[self.stage].name
+            );
+        }
+    }
+
+    /// Announces the countdown before each emergency check.
+    fn report_countdown(&self, state: MonitorState) {
+        let MonitorState::Emergency { retries_left, since, countdown_from, .. } = state else {
+            return;
+        };
+        // The availability policy has no tries to count down
+        if self.flap.is_flapping() || self.deferred || self.history.is_some() {
+            return;
+        }
+        let ctx = context! {
+            retries_left => retries_left,
+            remaining => self.info.max_outage.map(|max| max.saturating_sub(countdown_from.elapsed()).as_secs()),
+            outage_secs => since.elapsed().as_secs(),
+            ..self.event_context(state)
+        };
+        println!("{}", self.info.templates.render("emergency-remaining", ctx));
+    }
+
+    fn announce_countdown(&self, since: Instant, deadline: Instant) {
+        let left = deadline.saturating_duration_since(Instant::now()).as_secs();
+        let actions: Vec<String> = self.info.actions.iter().map(|action| action.to_string()).collect();
         println!(
-            "{} {} | up: {} | down: {} | next: {}s",
-            "[NORMAL]".bold().green(),
-            "OK".bold().green(),
-            succeeds,
-            failures,
-            secs
+            "{} Action due; running {} in {}s unless connectivity returns. Notifying logged-in users.",
+            "[EMERGENCY]".bold().red(),
+            actions.join(" -> "),
+            left
         );
-        sleep_with_progress(secs, info.progress, "[NORMAL]");
+        let ctx = context! { host => &self.host, outage_secs => since.elapsed().as_secs(), actions => actions, remaining => left };
+        self.broadcast(&self.info.templates.render("wall-countdown", ctx));
+    }
+
+    fn cancel_countdown(&self, reconnected: bool) {
+        let reason = if reconnected { "connectivity is back" } else { "the action is suppressed" };
+        println!("{} Countdown cancelled, {}.", "[EMERGENCY]".bold().green(), reason);
+        let ctx = context! { host => &self.host, reason => reason };
+        self.broadcast(&self.info.templates.render("wall-cancel", ctx));
+    }
+
+    /// Wall messages are only logged in a dry run.
+    fn broadcast(&self, message: &str) {
+        if self.info.dry_run.enabled() {
+            println!("{} would broadcast: {}", "[DRY-RUN]".bold().cyan(), message);
+        } else {
+            self.system.broadcast(message);
+        }
+    }
+
+    /// Runs the pre-action hooks and then the configured action pipeline, returning whether the
+    /// actions ran. Monitoring carries on afterwards: a failed check starts a fresh countdown,
+    /// a successful one counts towards recovery.
+    fn act(&self, state: MonitorState, since: Instant) -> bool {
+        if let Some(stage) = self.info.escalation.get(self.stage) {
+            println!(
+                "{} Stage {}/{} '{}' reached.",
+                "[ESCALATE]".bold().red(),
+                self.stage + 1,
+                self.info.escalation.len(),
+                stage.name
+            );
+        }
+        let ctx = context! {
+            outage_secs => since.elapsed().as_secs(),
+            max_outage => self.info.max_outage.map(|max| max.as_secs()),
+            actions => self.info.actions.iter().map(|action| action.to_string()).collect::<Vec<_>>(),
+            ..self.event_context(state)
+        };
+        println!("{}", self.info.templates.render("action", ctx));
+        let context = OutageContext {
+            failing_targets: self.failing.clone(),
+            since,
+            retries_used: self.retries_used,
+            policy: self.policy_name(),
+        };
+        if let Some(reason) = run_pre_action_hooks(&self.info, &context, self.system) {
+            println!("{} Actions vetoed by {}.", "[HOOK]".bold().yellow(), reason);
+            return false;
+        }
+        self.notifier.notify(NotifyEvent::Action, state, &self.info, Some(&self.report));
+        let actions = limit_actions(&self.info, self.system);
+        run_actions(&actions, self.system, self.info.dry_run, |outcome| {
+            record_action(outcome, self.metrics, self.system)
+        });
+        exit_after_dry_run(&self.info, self.metrics);
+        true
     }
 }
 
-/// Critical failure handler activated when connectivity is lost. Implements retry mechanism and system shutdown protocol.
-fn emergency_loop<S: System>(info: &MonitorConfig, metrics: &Arc<Mutex<Metrics>>, system: &S) {
-    let secs = info.emergency_interval_secs();
-    let mut time_left = info.emergency_retry_attempts();
-    println!(
-        "{} Connection lost. Entering emergency loop ({}s interval, {} tries).",
-        "[EMERGENCY]".bold().red(),
-        secs,
-        time_left
-    );
-    loop {
-        println!("{} {} tries remaining...", "[EMERGENCY]".bold().red(), time_left);
-        let (status, succeeds, failures) = check_status(info, metrics, system);
-        if status {
-            println!("{} Reconnected.", "[EMERGENCY]".bold().green());
-            break;
-        } else if time_left == 0 {
-            println!("{} Exceeded maximum retries. Shutting down...", "[EMERGENCY]".bold().red());
-            system.shutdown();
-            error("system shutdown failed - check permissions");
+/// Tracks which time-of-day profile is in effect and rebuilds the effective
+/// configuration from the base one whenever a profile boundary passes.
+struct ProfileSwitch {
+    base: MonitorConfig,
+    current: Option<String>,
+}
+
+impl ProfileSwitch {
+    fn new(base: MonitorConfig) -> Self {
+        Self { base, current: None }
+    }
+
+    /// Returns the new effective configuration when the active profile changed
+    /// since the last call, logging the switch.
+    fn poll(&mut self) -> Option<MonitorConfig> {
+        if self.base.profiles.is_empty() {
+            return None;
+        }
+        let active = active_profile(&self.base.profiles);
+        let name = active.map(|profile| profile.name.clone());
+        if name == self.current {
+            return None;
         }
+        let effective = match active {
+            Some(profile) => self.base.with_profile(profile),
+            None => self.base.clone(),
+        };
         println!(
-            "{} {} | up: {} | down: {} | next: {}s",
-            "[EMERGENCY]".bold().red(),
-            "DOWN".bold().red(),
-            succeeds,
-            failures,
-            secs
+            "{} Switched to {} | normal: {}s | emergency: {}s x{} | strict: {}",
+            "[PROFILE]".bold().cyan(),
+            name.as_deref().map(|n| format!("'{}'", n)).unwrap_or_else(|| "base configuration".to_string()),
+            effective.normal_interval_secs(),
+            effective.emergency_interval_secs(),
+            effective.emergency_retry_attempts(),
+            effective.strict
         );
-        add_one(metrics, MetricEvent::EmergencyLoopTimes);
-        if time_left > 0 {
-            time_left -= 1;
+        self.current = name;
+        Some(effective)
+    }
+}
+
+/// Why the emergency action must not run right now.
+enum Suppression<'a> {
+    Maintenance(&'a TimeWindow),
+    StartupGrace(Duration),
+}
+
+impl Suppression<'_> {
+    fn label(&self) -> &'static str {
+        match self {
+            Suppression::Maintenance(_) => "[MAINTENANCE]",
+            Suppression::StartupGrace(_) => "[GRACE]",
+        }
+    }
+
+    fn reason(&self) -> String {
+        match self {
+            Suppression::Maintenance(window) => format!("during maintenance window {}", window),
+            Suppression::StartupGrace(left) => format!("during the startup grace period ({}s left)", left.as_secs()),
         }
-        sleep_with_progress(secs, info.progress, "[EMERGENCY]");
     }
-    println!("{} Exiting {}sec emergency loop...", "[EMERGENCY]".bold().green(), secs);
 }
 
-fn sleep_with_progress(secs: u64, progress: bool, prefix: &str) {
+/// Maintenance windows and the startup grace period both hold back the emergency
+/// action while monitoring and reporting carry on.
+fn action_suppression<'a, S: System>(info: &'a MonitorConfig, system: &S) -> Option<Suppression<'a>> {
+    if let Some(left) = startup_grace_remaining(info, system) {
+        return Some(Suppression::StartupGrace(left));
+    }
+    info.maintenance_windows
+        .iter()
+        .find(|window| window.contains_now())
+        .map(Suppression::Maintenance)
+}
+
+/// Time left in the startup grace period, measured from process start or system boot.
+fn startup_grace_remaining<S: System>(info: &MonitorConfig, system: &S) -> Option<Duration> {
+    let grace = info.startup_grace?;
+    let elapsed = match info.grace_clock {
+        GraceClock::Uptime => system.uptime().unwrap_or_else(|| process_started().elapsed()),
+        GraceClock::Process => process_started().elapsed(),
+    };
+    let left = grace.saturating_sub(elapsed);
+    (!left.is_zero()).then_some(left)
+}
+
+/// Jittered emergency interval, shortened so the next probe lands on the `max_outage` deadline.
+fn emergency_delay(info: &MonitorConfig, countdown_from: Instant, jitter: &mut Jitter) -> Duration {
+    let delay = jitter.apply(info.emergency_interval);
+    match info.max_outage {
+        Some(max) => delay.min(max.saturating_sub(countdown_from.elapsed())).max(Duration::from_secs(1)),
+        None => delay,
+    }
+}
+
+/// Adds an action that really ran to the attempt log. After a power action the report file is
+/// written straight away, since the machine may go down before pingdown exits normally.
+fn record_action<S: System>(outcome: &ActionOutcome, metrics: &Arc<Mutex<Metrics>>, system: &S) {
+    if outcome.result == ActionResult::DryRun || outcome.action.kind == ActionKind::LogOnly {
+        return;
+    }
+    let Ok(mut metrics) = metrics.lock() else { return };
+    metrics.action_log.push(outcome.clone());
+    if outcome.action.kind.is_power() {
+        if let Err(err) = system.write_file(Path::new(METRICS_FILE), &metrics.report()) {
+            println!("{} Could not write {}: {}", "[ACTION]".bold().red(), METRICS_FILE, err);
+        }
+    }
+}
+
+/// Ends a dry run in `exit` mode once the actions have been logged, flushing metrics first.
+fn exit_after_dry_run(info: &MonitorConfig, metrics: &Arc<Mutex<Metrics>>) {
+    if info.dry_run != DryRun::Exit {
+        return;
+    }
+    println!("{} Action reached; exiting with code {}.", "[DRY-RUN]".bold().cyan(), EXIT_DRY_RUN);
+    if let Ok(metrics) = metrics.lock() {
+        println!("{}", metrics.summary(&info.templates));
+        metrics.write();
+    }
+    std::process::exit(EXIT_DRY_RUN);
+}
+
+/// Names the parents that were down and how many dependent targets they took with them.
+fn root_cause(report: &CycleReport) -> String {
+    report
+        .root_causes()
+        .iter()
+        .map(|root| {
+            let children = report.unreachable.iter().filter(|(_, r)| r == root).count();
+            format!("{} ({} target(s) unreachable)", root, children)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Sleeps a random fraction of `initial_offset` so hosts started together spread out.
+fn initial_offset(info: &MonitorConfig, jitter: &mut Jitter, prefix: &str) {
+    let delay = jitter.offset(info.initial_offset);
+    if delay.is_zero() {
+        return;
+    }
+    println!("{} Initial offset of {}s before the first check...", prefix, delay.as_secs());
+    sleep_with_progress(delay, info.progress, prefix);
+}
+
+/// Prints a single alert when the link starts or stops flapping.
+fn report_flap_change(change: FlapChange, flap: &FlapDetector) {
+    match change {
+        FlapChange::Started => println!(
+            "{} Link changed state {} times within {}s; suppressing per-transition messages.",
+            "[FLAPPING]".bold().yellow(),
+            flap.transitions(),
+            flap.window_secs()
+        ),
+        FlapChange::Cleared => println!(
+            "{} No state changes for {}s; link is stable again.",
+            "[FLAPPING]".bold().green(),
+            flap.window_secs()
+        ),
+        FlapChange::Unchanged => {}
+    }
+}
+
+fn sleep_with_progress(delay: Duration, progress: bool, prefix: &str) {
     if !progress {
-        println!("{} {} secs left for the next check...", prefix, secs);
-        thread::sleep(Duration::from_secs(secs));
+        println!("{} {} secs left for the next check...", prefix, delay.as_secs());
+        thread::sleep(delay);
         return;
     }
     let spinner = ["-", "\\", "|", "/"]; // simple spinner
-    let mut elapsed = 0u64;
+    let tick = Duration::from_millis(200);
+    let start = Instant::now();
     let mut i = 0usize;
-    println!("{} Waiting {}s...", prefix, secs);
-    while elapsed < secs {
-        print!("\r{} {} {}s", prefix, spinner[i % spinner.len()], secs - elapsed);
+    println!("{} Waiting {}s...", prefix, delay.as_secs());
+    while start.elapsed() < delay {
+        let left = delay.saturating_sub(start.elapsed());
+        print!("\r{} {} {}s", prefix, spinner[i % spinner.len()], left.as_secs_f64().ceil() as u64);
         let _ = io::stdout().flush();
-        thread::sleep(Duration::from_millis(200));
+        thread::sleep(tick.min(left));
         i += 1;
-        if i % 5 == 0 {
-            elapsed += 1; // advance roughly 1s per 5 ticks of 200ms
-        }
     }
     print!("\r{} Done.            \n", prefix);
     let _ = io::stdout().flush();
 }
 
+/// Runs the emergency phase on its own, starting from a fresh countdown, until the link recovers.
 pub fn test_emergency_loop<S: System>(info: &MonitorConfig, metrics: &Arc<Mutex<Metrics>>, system: &S) {
-    emergency_loop(info, metrics, system);
+    let mut driver = Driver::new(info.clone(), metrics, system, false);
+    let now = Instant::now();
+    let mut state = MonitorState::Emergency {
+        failed: driver.info.fail_threshold_cycles(),
+        recovered: 0,
+        retries_left: driver.info.emergency_retry_attempts(),
+        since: now,
+        countdown_from: now,
+    };
+    driver.report_entry(state);
+    loop {
+        let (next, delay) = driver.cycle(state);
+        if let MonitorState::Recovered { .. } = next {
+            break;
+        }
+        driver.sleep(next, delay);
+        state = next;
+    }
 }
@@ -0,0 +1,171 @@
+use std::thread;
+use std::time::Duration;
+use chrono::Local;
+use colored::Colorize;
+use rumqttc::{Client, Event, LastWill, MqttOptions, Packet, QoS};
+use serde::{Deserialize, Serialize};
+
+use crate::notify::{host_name, split_host_port, target_results};
+use crate::ping::CycleReport;
+use crate::state::MonitorState;
+
+pub const DEFAULT_MQTT_PORT: u16 = 1883;
+/// Pause before reconnecting after the broker could not be reached
+pub const MQTT_RECONNECT_SECS: u64 = 5;
+/// Messages held while the broker is away; older state is dropped beyond that
+const MQTT_QUEUE: usize = 64;
+
+/// Raw broker settings as written in the configuration file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MqttSpec {
+    /// Host name or address of the broker, optionally with `:port` (`[addr]:port` for IPv6)
+    pub broker: String,
+    pub port: Option<u16>,
+    /// Topics are published below this prefix; defaults to `pingdown/<host name>`
+    #[serde(alias = "topic-prefix")]
+    pub prefix: Option<String>,
+    #[serde(alias = "client-id")]
+    pub client_id: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+/// A broker that state is published to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mqtt {
+    pub host: String,
+    pub port: u16,
+    pub prefix: String,
+    pub client_id: String,
+    pub credentials: Option<(String, String)>,
+}
+
+impl Mqtt {
+    /// Validates the raw settings; the error names the offending sub-field.
+    pub fn parse(spec: &MqttSpec) -> Result<Self, (&'static str, String)> {
+        let broker = spec.broker.trim();
+        let Some((host, port)) = split_host_port(broker) else {
+            return Err(("broker", format!("invalid port in '{}'", broker)));
+        };
+        if host.is_empty() {
+            return Err(("broker", "MQTT broker cannot be empty".to_string()));
+        }
+        let port = match spec.port.or(port) {
+            Some(0) => return Err(("port", "MQTT port must be greater than zero".to_string())),
+            Some(port) => port,
+            None => DEFAULT_MQTT_PORT,
+        };
+        let host_name = host_name();
+        let prefix = match spec.prefix.as_deref().map(|prefix| prefix.trim().trim_end_matches('/')) {
+            Some("") => return Err(("prefix", "topic prefix cannot be empty".to_string())),
+            Some(prefix) if prefix.contains(['+', '#']) => {
+                return Err(("prefix", format!("'{}' must not contain the wildcards + or #", prefix)));
+            }
+            Some(prefix) => prefix.to_string(),
+            None => format!("pingdown/{}", host_name),
+        };
+        let credentials = match (&spec.username, &spec.password) {
+            (Some(user), password) => Some((user.clone(), password.clone().unwrap_or_default())),
+            (None, Some(_)) => return Err(("username", "a username is required with a password".to_string())),
+            (None, None) => None,
+        };
+        let client_id = spec.client_id.clone().unwrap_or_else(|| format!("pingdown-{}", host_name));
+        Ok(Self { host: host.to_string(), port, prefix, client_id, credentials })
+    }
+
+    pub fn status_topic(&self) -> String { format!("{}/status", self.prefix) }
+
+    pub fn state_topic(&self) -> String { format!("{}/state", self.prefix) }
+
+    /// Topic of one target; characters with a meaning in MQTT topics are replaced by `_`.
+    pub fn target_topic(&self, target: &str) -> String {
+        format!("{}/targets/{}", self.prefix, target.replace(['/', '+', '#'], "_"))
+    }
+}
+
+/// Retained payload of the state topic.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StatePayload {
+    pub state: &'static str,
+    pub ok: bool,
+    pub succeeds: u64,
+    pub failures: u64,
+    pub failing_targets: Vec<String>,
+    /// Local time of the cycle, RFC 3339
+    pub time: String,
+}
+
+/// Publishes the monitor's state to the broker from a connection thread of its own.
+/// Publishing never blocks: while the broker is away, messages wait in a short queue
+/// and the newest ones are dropped once it is full.
+pub struct MqttPublisher {
+    client: Client,
+    config: Mqtt,
+}
+
+impl MqttPublisher {
+    /// Starts connecting in the background; `<prefix>/status` reads `online` while connected
+    /// and the broker sets it to `offline` through the Last Will when the connection drops.
+    pub fn start(config: &Mqtt) -> Self {
+        let mut options = MqttOptions::new(config.client_id.clone(), config.host.clone(), config.port);
+        options.set_keep_alive(Duration::from_secs(30));
+        options.set_last_will(LastWill::new(config.status_topic(), "offline", QoS::AtLeastOnce, true));
+        if let Some((user, password)) = &config.credentials {
+            options.set_credentials(user.clone(), password.clone());
+        }
+        let (client, mut connection) = Client::new(options, MQTT_QUEUE);
+        let announcer = client.clone();
+        let (status, broker) = (config.status_topic(), format!("{}:{}", config.host, config.port));
+        thread::spawn(move || {
+            let mut reachable = None;
+            for event in connection.iter() {
+                match event {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                        println!("{} Connected to {}.", "[MQTT]".bold().green(), broker);
+                        reachable = Some(true);
+                        let _ = announcer.try_publish(status.as_str(), QoS::AtLeastOnce, true, "online");
+                    }
+                    Ok(_) => {}
+                    Err(err) => {
+                        if reachable != Some(false) {
+                            println!(
+                                "{} {} unreachable ({}); retrying every {}s.",
+                                "[MQTT]".bold().yellow(),
+                                broker,
+                                err,
+                                MQTT_RECONNECT_SECS
+                            );
+                        }
+                        reachable = Some(false);
+                        thread::sleep(Duration::from_secs(MQTT_RECONNECT_SECS));
+                    }
+                }
+            }
+        });
+        Self { client, config: config.clone() }
+    }
+
+    /// Publishes the state and the status of every target after a cycle, all retained.
+    pub fn publish_cycle(&self, state: MonitorState, targets: &[String], report: &CycleReport) {
+        let payload = StatePayload {
+            state: state.name(),
+            ok: report.ok,
+            succeeds: report.succeeds,
+            failures: report.failures,
+            failing_targets: report.failing_targets(),
+            time: Local::now().to_rfc3339(),
+        };
+        if let Ok(json) = serde_json::to_string(&payload) {
+            self.publish(self.config.state_topic(), json);
+        }
+        for target in target_results(targets, Some(report)) {
+            self.publish(self.config.target_topic(&target.target), target.status.to_string());
+        }
+    }
+
+    fn publish(&self, topic: String, payload: String) {
+        // A full queue means the broker has been away for a while; the next cycle retries
+        let _ = self.client.try_publish(topic, QoS::AtLeastOnce, true, payload);
+    }
+}
@@ -0,0 +1,272 @@
+//! Notifications about the monitor's state, sent off the monitoring thread so a slow or
+//! unreachable receiver never delays a check.
+
+use std::net::Ipv6Addr;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use chrono::{Local, TimeDelta};
+use colored::Colorize;
+use crossbeam_channel::{unbounded, Sender};
+use serde::{Serialize, Serializer};
+
+use crate::config::MonitorConfig;
+use crate::mail::send_alert;
+use crate::ping::CycleReport;
+use crate::state::MonitorState;
+use crate::webhook::{deliver, enqueue, flush_queue, Webhook};
+
+/// Moments a notification is sent for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotifyEvent {
+    /// pingdown started monitoring
+    Startup,
+    /// Connectivity was lost and the emergency loop began
+    Emergency,
+    /// The actions are about to run
+    Action,
+    /// Connectivity came back after an emergency
+    Recovery,
+}
+
+impl NotifyEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotifyEvent::Startup => "startup",
+            NotifyEvent::Emergency => "emergency",
+            NotifyEvent::Action => "action",
+            NotifyEvent::Recovery => "recovery",
+        }
+    }
+}
+
+/// How one target fared in the last cycle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TargetResult {
+    pub target: String,
+    /// `up`, `down`, `unreachable`, or `unknown` before the first check
+    pub status: &'static str,
+    /// Parent that was down, for unreachable targets
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root_cause: Option<String>,
+    /// Round-trip time of the last answer, serialized in milliseconds
+    #[serde(rename = "rtt_ms", serialize_with = "millis", skip_serializing_if = "Option::is_none")]
+    pub rtt: Option<Duration>,
+}
+
+fn millis<S: Serializer>(rtt: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+    match rtt {
+        Some(rtt) => serializer.serialize_f64(rtt.as_secs_f64() * 1000.0),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Status of every target in `report`, in configuration order.
+pub fn target_results(targets: &[String], report: Option<&CycleReport>) -> Vec<TargetResult> {
+    targets
+        .iter()
+        .map(|target| {
+            let (status, root_cause) = match report {
+                None => ("unknown", None),
+                Some(report) if report.down.contains(target) => ("down", None),
+                Some(report) => match report.unreachable.iter().find(|(name, _)| name == target) {
+                    Some((_, root)) => ("unreachable", Some(root.clone())),
+                    None => ("up", None),
+                },
+            };
+            let rtt = report.and_then(|report| report.rtt(target));
+            TargetResult { target: target.clone(), status, root_cause, rtt }
+        })
+        .collect()
+}
+
+/// What a notification says, serialized as the webhook payload and rendered into alert mails.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Notification {
+    pub event: NotifyEvent,
+    pub host: String,
+    /// Local time of the event, RFC 3339
+    pub time: String,
+    /// State the monitor is in
+    pub state: &'static str,
+    pub targets: Vec<TargetResult>,
+    pub failing_targets: Vec<String>,
+    pub failed_cycles: u32,
+    /// Emergency checks left before the actions run
+    pub retries_left: Option<u32>,
+    /// Start of the outage, RFC 3339, while one is going on or has just ended
+    pub outage_start: Option<String>,
+    pub outage_secs: u64,
+    /// Actions configured to run once the outage is long enough
+    pub actions: Vec<String>,
+}
+
+impl Notification {
+    pub fn new(
+        event: NotifyEvent,
+        state: MonitorState,
+        cfg: &MonitorConfig,
+        report: Option<&CycleReport>,
+        host: &str,
+    ) -> Self {
+        let (failed_cycles, retries_left, outage) = match state {
+            MonitorState::Emergency { failed, retries_left, since, .. } => {
+                (failed, Some(retries_left), Some(since.elapsed()))
+            }
+            MonitorState::Suspect { failed, since } => (failed, None, Some(since.elapsed())),
+            MonitorState::Countdown { failed, since, .. } | MonitorState::Acting { failed, since } => {
+                (failed, Some(0), Some(since.elapsed()))
+            }
+            MonitorState::Recovered { outage } => (0, None, Some(outage)),
+            MonitorState::Normal => (0, None, None),
+        };
+        let now = Local::now();
+        Self {
+            event,
+            host: host.to_string(),
+            time: now.to_rfc3339(),
+            state: state.name(),
+            targets: target_results(&cfg.targets, report),
+            failing_targets: report.map(CycleReport::failing_targets).unwrap_or_default(),
+            failed_cycles,
+            retries_left,
+            outage_start: outage.map(|outage| (now - TimeDelta::from_std(outage).unwrap_or_default()).to_rfc3339()),
+            outage_secs: outage.map(|outage| outage.as_secs()).unwrap_or(0),
+            actions: cfg.actions.iter().map(|action| action.to_string()).collect(),
+        }
+    }
+}
+
+/// Work for the notification thread.
+enum Job {
+    /// Sends a notification
+    Deliver(Notification),
+    /// Retries the webhook queue
+    FlushQueue,
+}
+
+/// Hands notifications to a background worker that delivers them to the configured webhooks
+/// and mail relay. Without either nothing is started and sending is a no-op.
+pub struct Notifier {
+    host: String,
+    sender: Option<Sender<Job>>,
+    queue: Option<PathBuf>,
+    /// A queue flush is waiting for the worker, so successful checks do not pile them up
+    flush_pending: Arc<AtomicBool>,
+}
+
+impl Notifier {
+    pub fn start(cfg: &MonitorConfig) -> Self {
+        let host = host_name();
+        if cfg.webhooks.is_empty() && cfg.smtp.is_none() {
+            return Self { host, sender: None, queue: None, flush_pending: Arc::default() };
+        }
+        let (sender, receiver) = unbounded::<Job>();
+        let webhooks = cfg.webhooks.clone();
+        let queue = cfg.webhook_queue.clone().filter(|_| !webhooks.is_empty());
+        let smtp = cfg.smtp.clone();
+        let templates = cfg.templates.clone();
+        let worker_queue = queue.clone();
+        let flush_pending = Arc::new(AtomicBool::new(false));
+        let pending = Arc::clone(&flush_pending);
+        thread::spawn(move || {
+            for job in receiver {
+                let notification = match job {
+                    Job::Deliver(notification) => notification,
+                    Job::FlushQueue => {
+                        pending.store(false, Ordering::Relaxed);
+                        if let Some(path) = &worker_queue {
+                            flush_queue(path);
+                        }
+                        continue;
+                    }
+                };
+                if !webhooks.is_empty() {
+                    if let Ok(body) = serde_json::to_string(&notification) {
+                        deliver_all(&webhooks, worker_queue.as_ref(), &body);
+                    }
+                }
+                if let Some(smtp) = &smtp {
+                    send_alert(smtp, &notification, &templates);
+                }
+            }
+        });
+        Self { host, sender: Some(sender), queue, flush_pending }
+    }
+
+    /// Queues a notification for delivery and returns at once.
+    pub fn notify(&self, event: NotifyEvent, state: MonitorState, cfg: &MonitorConfig, report: Option<&CycleReport>) {
+        let Some(sender) = &self.sender else { return };
+        let _ = sender.send(Job::Deliver(Notification::new(event, state, cfg, report, &self.host)));
+    }
+
+    /// Sends queued webhook deliveries once a check succeeds, without waiting for the next event.
+    pub fn connected(&self) {
+        let (Some(sender), Some(queue)) = (&self.sender, &self.queue) else { return };
+        if queue.exists() && !self.flush_pending.swap(true, Ordering::Relaxed) {
+            let _ = sender.send(Job::FlushQueue);
+        }
+    }
+}
+
+/// Earlier deliveries waiting in the queue go first, so receivers see events in order.
+fn deliver_all(webhooks: &[Webhook], queue: Option<&PathBuf>, body: &str) {
+    if let Some(path) = queue {
+        flush_queue(path);
+    }
+    for hook in webhooks {
+        let Err(err) = deliver(hook, body) else { continue };
+        match (queue, err.retryable) {
+            (_, false) => println!("{} {} rejected the notification: {}", "[WEBHOOK]".bold().red(), hook.url, err),
+            (Some(path), true) => match enqueue(path, hook, body) {
+                Ok(()) => println!("{} {} unreachable ({}); queued for later.", "[WEBHOOK]".bold().yellow(), hook.url, err),
+                Err(io) => println!("{} {} failed ({}) and could not be queued: {}", "[WEBHOOK]".bold().red(), hook.url, err, io),
+            },
+            (None, true) => println!(
+                "{} {} failed after {} attempt(s): {}",
+                "[WEBHOOK]".bold().red(),
+                hook.url,
+                hook.retries + 1,
+                err
+            ),
+        }
+    }
+}
+
+/// Best-effort name of this machine for the payload.
+pub fn host_name() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| std::fs::read_to_string("/proc/sys/kernel/hostname").ok())
+        .or_else(|| {
+            let output = std::process::Command::new("hostname").output().ok()?;
+            Some(String::from_utf8_lossy(&output.stdout).into_owned())
+        })
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Splits `host[:port]` for the mail relay and the MQTT broker. IPv6 addresses take the
+/// port as `[addr]:port`; a bare one is a host without a port. `None` when the port is
+/// not a number.
+pub fn split_host_port(address: &str) -> Option<(&str, Option<u16>)> {
+    if let Some(rest) = address.strip_prefix('[') {
+        let (host, rest) = rest.split_once(']')?;
+        return match rest {
+            "" => Some((host, None)),
+            _ => Some((host, Some(rest.strip_prefix(':')?.parse().ok()?))),
+        };
+    }
+    if address.parse::<Ipv6Addr>().is_ok() {
+        return Some((address, None));
+    }
+    match address.rsplit_once(':') {
+        Some((host, port)) => Some((host, Some(port.parse().ok()?))),
+        None => Some((address, None)),
+    }
+}
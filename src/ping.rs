@@ -1,11 +1,69 @@
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use crate::config::MonitorConfig;
+use crate::config::{MonitorConfig, GATEWAY_TARGET};
 use crate::runtime::{add_one, MetricEvent, Metrics};
 use crate::system::{error, System};
 
-/// Tests connectivity to a single target using system ping command
-fn get_status<S: System>(ip: &str, system: &S, cfg: &MonitorConfig) -> bool {
+/// Result of probing one target within a cycle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Probe {
+    /// Answered, with the round-trip time when the ping output reports one
+    Up { rtt: Option<Duration> },
+    Down,
+    /// Not probed because `root`, a parent it depends on, is down
+    Unreachable { root: String },
+}
+
+/// Outcome of a full probing cycle across all targets.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CycleReport {
+    pub ok: bool,
+    pub succeeds: u64,
+    /// Targets that were probed and failed on their own
+    pub failures: u64,
+    /// Names of those failed targets
+    pub down: Vec<String>,
+    /// `(target, root cause)` for targets skipped because a parent is down
+    pub unreachable: Vec<(String, String)>,
+    /// `(target, round-trip time)` for targets that answered and reported one
+    pub rtts: Vec<(String, Duration)>,
+}
+
+impl CycleReport {
+    /// Distinct parents that took dependent targets down, in target order.
+    pub fn root_causes(&self) -> Vec<&str> {
+        let mut roots: Vec<&str> = Vec::new();
+        for (_, root) in &self.unreachable {
+            if !roots.contains(&root.as_str()) {
+                roots.push(root);
+            }
+        }
+        roots
+    }
+
+    /// Every target that did not answer this cycle, down or unreachable.
+    pub fn failing_targets(&self) -> Vec<String> {
+        self.down.iter().cloned().chain(self.unreachable.iter().map(|(target, _)| target.clone())).collect()
+    }
+
+    /// Round-trip time measured for `target` this cycle.
+    pub fn rtt(&self, target: &str) -> Option<Duration> {
+        self.rtts.iter().find(|(name, _)| name == target).map(|(_, rtt)| *rtt)
+    }
+}
+
+/// Round-trip time from ping output: `time=12.3 ms` on Unix, `time=12ms` or `time<1ms` on Windows.
+pub fn parse_rtt(output: &str) -> Option<Duration> {
+    let start = output.find("time=").or_else(|| output.find("time<"))? + 5;
+    let number: String = output[start..].chars().take_while(|c| c.is_ascii_digit() || *c == '.').collect();
+    let millis: f64 = number.parse().ok()?;
+    Some(Duration::from_secs_f64(millis / 1000.0))
+}
+
+/// Tests connectivity to a single target using system ping command; `None` when it did not answer
+fn get_status<S: System>(ip: &str, system: &S, cfg: &MonitorConfig) -> Option<Option<Duration>> {
     let command = system.build_ping_command(ip);
     let message = if cfg.quiet || cfg.status_only { None } else { Some(format!("Pinging {}...", ip)) };
     let output = match system.run_shell_command(&command, message.as_deref()) {
@@ -17,34 +75,81 @@ fn get_status<S: System>(ip: &str, system: &S, cfg: &MonitorConfig) -> bool {
     if cfg.verbose > 0 && !cfg.status_only && !cfg.quiet {
         if ok { println!("Success."); } else { println!("Request timed out."); }
     }
-    ok
+    ok.then(|| parse_rtt(&status))
 }
 
-/// Evaluates connectivity status across multiple targets according to monitoring mode
-pub fn check_status<S: System>(cfg: &MonitorConfig, metrics: &Arc<Mutex<Metrics>>, system: &S) -> (bool, u64, u64) {
-    let mut status_vec: Vec<bool> = vec![];
-    for ip in &cfg.targets {
-        let status = get_status(ip, system, cfg);
-        status_vec.push(status);
-    }
-    let mut succeeds = 0;
-    let mut failures = 0;
-    let status = if cfg.strict {
-        for status in status_vec {
-            match status {
-                true => { succeeds += 1; add_one(metrics, MetricEvent::Succeeds); }
-                false => { failures += 1; add_one(metrics, MetricEvent::Failures); }
+/// Probes `target` after its parent chain, reusing results already gathered this cycle.
+fn probe<S: System>(target: &str, cfg: &MonitorConfig, system: &S, seen: &mut HashMap<String, Probe>) -> Probe {
+    if let Some(result) = seen.get(target) {
+        return result.clone();
+    }
+    let result = match cfg.dependencies.get(target).map(|parent| (parent, probe(parent, cfg, system, seen))) {
+        Some((parent, Probe::Down)) => Probe::Unreachable { root: parent.clone() },
+        Some((_, Probe::Unreachable { root })) => Probe::Unreachable { root },
+        _ => probe_host(target, cfg, system),
+    };
+    if let Probe::Unreachable { root } = &result {
+        if !cfg.quiet && !cfg.status_only {
+            println!("Skipping {}: unreachable due to parent {}.", target, root);
+        }
+    }
+    seen.insert(target.to_string(), result.clone());
+    result
+}
+
+fn probe_host<S: System>(target: &str, cfg: &MonitorConfig, system: &S) -> Probe {
+    let host = if target == GATEWAY_TARGET {
+        match system.default_gateway() {
+            Some(gateway) => gateway,
+            None => {
+                if !cfg.quiet && !cfg.status_only {
+                    println!("No default gateway found for {}.", GATEWAY_TARGET);
+                }
+                return Probe::Down;
             }
         }
-        failures == 0
     } else {
-        for status in status_vec {
-            match status {
-                true => { succeeds += 1; add_one(metrics, MetricEvent::Succeeds); }
-                false => { failures += 1; add_one(metrics, MetricEvent::Failures); }
+        target.to_string()
+    };
+    match get_status(&host, system, cfg) {
+        Some(rtt) => Probe::Up { rtt },
+        None => Probe::Down,
+    }
+}
+
+/// Probes every target, skipping those whose parent is down, and decides the cycle
+/// according to the monitoring mode. Skipped targets never count as failures of their
+/// own, but a strict cycle still fails while any target is unreachable.
+pub fn check_targets<S: System>(cfg: &MonitorConfig, metrics: &Arc<Mutex<Metrics>>, system: &S) -> CycleReport {
+    let mut seen = HashMap::new();
+    let mut report = CycleReport::default();
+    for target in &cfg.targets {
+        match probe(target, cfg, system, &mut seen) {
+            Probe::Up { rtt } => {
+                report.succeeds += 1;
+                add_one(metrics, MetricEvent::Succeeds);
+                if let Some(rtt) = rtt {
+                    report.rtts.push((target.clone(), rtt));
+                }
+            }
+            Probe::Down => {
+                report.failures += 1;
+                report.down.push(target.clone());
+                add_one(metrics, MetricEvent::Failures);
             }
+            Probe::Unreachable { root } => report.unreachable.push((target.clone(), root)),
         }
-        succeeds > 0
+    }
+    report.ok = if cfg.strict {
+        report.failures == 0 && report.unreachable.is_empty()
+    } else {
+        report.succeeds > 0
     };
-    (status, succeeds, failures)
+    report
+}
+
+/// Evaluates connectivity status across multiple targets according to monitoring mode
+pub fn check_status<S: System>(cfg: &MonitorConfig, metrics: &Arc<Mutex<Metrics>>, system: &S) -> (bool, u64, u64) {
+    let report = check_targets(cfg, metrics, system);
+    (report.ok, report.succeeds, report.failures)
 }
@@ -0,0 +1,109 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use colored::Colorize;
+use serde::Deserialize;
+
+use crate::action::{ActionKind, ActionResult};
+use crate::config::MonitorConfig;
+use crate::ping::{check_targets, CycleReport};
+use crate::runtime::Metrics;
+use crate::system::System;
+
+pub const DEFAULT_SETTLE_SECS: u64 = 10;
+pub const DEFAULT_REMEDIATION_TIMEOUT_SECS: u64 = 60;
+
+/// Raw remediation step as written in the configuration file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RemediationSpec {
+    /// Label for the output; defaults to the command
+    pub name: Option<String>,
+    pub command: String,
+    /// Seconds to wait after the command before probing again
+    #[serde(alias = "settle-secs")]
+    pub settle: Option<u64>,
+    #[serde(alias = "timeout-secs")]
+    pub timeout: Option<u64>,
+}
+
+/// One attempt to repair the network before the actions run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemediationStep {
+    pub name: String,
+    pub command: String,
+    pub settle: Duration,
+    pub timeout: Duration,
+}
+
+impl RemediationStep {
+    pub fn new(command: impl Into<String>) -> Self {
+        let command = command.into();
+        Self {
+            name: command.clone(),
+            command,
+            settle: Duration::from_secs(DEFAULT_SETTLE_SECS),
+            timeout: Duration::from_secs(DEFAULT_REMEDIATION_TIMEOUT_SECS),
+        }
+    }
+
+    /// Validates a raw entry; the error names the offending sub-field.
+    pub fn parse(spec: &RemediationSpec) -> Result<Self, (&'static str, String)> {
+        let command = spec.command.trim();
+        if command.is_empty() {
+            return Err(("command", "remediation command cannot be empty".to_string()));
+        }
+        let name = match spec.name.as_deref().map(str::trim) {
+            Some("") => return Err(("name", "remediation name cannot be empty".to_string())),
+            Some(name) => name.to_string(),
+            None => command.to_string(),
+        };
+        let timeout = match spec.timeout {
+            Some(0) => return Err(("timeout", "remediation timeout must be greater than zero seconds".to_string())),
+            Some(secs) => Duration::from_secs(secs),
+            None => Duration::from_secs(DEFAULT_REMEDIATION_TIMEOUT_SECS),
+        };
+        let settle = Duration::from_secs(spec.settle.unwrap_or(DEFAULT_SETTLE_SECS));
+        Ok(Self { name, command: command.to_string(), settle, timeout })
+    }
+}
+
+/// Runs the remediation steps in order, probing the targets after each one has settled.
+/// Returns the report of the probe that found connectivity restored, or `None` when every
+/// step was tried and the network is still down.
+pub fn run_remediation<S: System>(cfg: &MonitorConfig, metrics: &Arc<Mutex<Metrics>>, system: &S) -> Option<CycleReport> {
+    let total = cfg.remediation.len();
+    for (idx, step) in cfg.remediation.iter().enumerate() {
+        println!("{} {}/{} {}...", "[REMEDIATE]".bold().yellow(), idx + 1, total, step.name);
+        if cfg.dry_run.enabled() {
+            println!(
+                "{} would run: {} (timeout {}s, settle {}s)",
+                "[DRY-RUN]".bold().cyan(),
+                step.command,
+                step.timeout.as_secs(),
+                step.settle.as_secs()
+            );
+            continue;
+        }
+        match system.run_action(&ActionKind::RunCommand(step.command.clone()), step.timeout) {
+            ActionResult::Ok | ActionResult::DryRun => {}
+            ActionResult::Failed(reason) => println!("{} {} failed: {}", "[REMEDIATE]".bold().red(), step.name, reason),
+            ActionResult::TimedOut => {
+                println!("{} {} timed out after {}s", "[REMEDIATE]".bold().red(), step.name, step.timeout.as_secs())
+            }
+        }
+        system.sleep_secs(step.settle.as_secs());
+        let report = check_targets(cfg, metrics, system);
+        if report.ok {
+            println!(
+                "{} Connectivity restored after '{}' | up: {} | down: {}",
+                "[REMEDIATE]".bold().green(),
+                step.name,
+                report.succeeds,
+                report.failures
+            );
+            return Some(report);
+        }
+        println!("{} Still down after '{}'.", "[REMEDIATE]".bold().red(), step.name);
+    }
+    None
+}
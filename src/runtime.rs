@@ -1,7 +1,25 @@
+use std::collections::VecDeque;
 use std::fs;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use minijinja::context;
 
+use crate::action::ActionOutcome;
+use crate::config::AvailabilityBasis;
 use crate::system::error;
+use crate::template::Templates;
+
+/// File the final metrics are written to, in the working directory
+pub const METRICS_FILE: &str = "pingdown_runtime_info.txt";
+/// State kept across restarts, next to the metrics file
+pub const STATE_FILE: &str = "pingdown_state.json";
+
+static PROCESS_START: OnceLock<Instant> = OnceLock::new();
+
+/// Records the process start on first use; call early so grace periods measure from launch.
+pub fn process_started() -> Instant {
+    *PROCESS_START.get_or_init(Instant::now)
+}
 
 /// Tracks various runtime metrics for monitoring and reporting.
 #[derive(Debug)]
@@ -10,6 +28,10 @@ pub struct Metrics {
     pub total_failures: u64,
     pub total_normal_loop_times: u64,
     pub total_emergency_loop_times: u64,
+    /// Escalation stages whose actions ran; `None` without an escalation ladder
+    pub total_stage_escalations: Option<u64>,
+    /// Every action that really ran, with the commands tried for it
+    pub action_log: Vec<ActionOutcome>,
 }
 
 /// Event types that can increment runtime metrics.
@@ -22,6 +44,12 @@ pub enum MetricEvent {
     NormalLoopTimes,
     /// Error recovery cycles
     EmergencyLoopTimes,
+    /// Moves up the escalation ladder
+    StageEscalations,
+}
+
+impl Default for Metrics {
+    fn default() -> Self { Self::new() }
 }
 
 impl Metrics {
@@ -32,20 +60,29 @@ impl Metrics {
             total_failures: 0,
             total_normal_loop_times: 0,
             total_emergency_loop_times: 0,
+            total_stage_escalations: None,
+            action_log: Vec::new(),
         }
     }
 
     /// Prints current metrics to stdout in debug format.
     pub fn output(&self) { println!("{:#?}", self); }
 
-    /// Writes metrics to file if any counter is non-zero.
+    /// Writes metrics to file if any counter is non-zero or an action ran.
     /// Creates/overwrites 'pingdown_runtime_info.txt' with debug-formatted data.
     /// Shows error message if file write fails (e.g., permission issues).
     pub fn write(&self) {
         match self {
-            Metrics { total_succeeds: 0, total_failures: 0, total_normal_loop_times: 0, total_emergency_loop_times: 0 } => {}
+            Metrics {
+                total_succeeds: 0,
+                total_failures: 0,
+                total_normal_loop_times: 0,
+                total_emergency_loop_times: 0,
+                total_stage_escalations: None | Some(0),
+                action_log,
+            } if action_log.is_empty() => {}
             _ => {
-                match fs::write("pingdown_runtime_info.txt", format!("{:#?}", self)) {
+                match fs::write(METRICS_FILE, self.report()) {
                     Ok(_) => {}
                     Err(err) => error(&format!("writing output file[{}], please check your permission.", err)),
                 }
@@ -53,18 +90,34 @@ impl Metrics {
         }
     }
 
+    /// Contents of the metrics file, including the action attempt log.
+    pub fn report(&self) -> String { format!("{:#?}", self) }
+
     /// Human-readable summary for final report
-    pub fn summary_string(&self) -> String {
-        format!(
-            "Summary -> up: {} | down: {} | normal_loops: {} | emergency_loops: {}",
-            self.total_succeeds,
-            self.total_failures,
-            self.total_normal_loop_times,
-            self.total_emergency_loop_times
-        )
+    pub fn summary_string(&self) -> String { self.summary(&Templates::default()) }
+
+    /// The final summary rendered with the `summary` template.
+    pub fn summary(&self, templates: &Templates) -> String {
+        let ctx = context! {
+            up => self.total_succeeds,
+            down => self.total_failures,
+            normal_loops => self.total_normal_loop_times,
+            emergency_loops => self.total_emergency_loop_times,
+            escalations => self.total_stage_escalations,
+        };
+        templates.render("summary", ctx)
     }
 }
 
+/// Starts counting escalations so the summary reports them, even when none happen.
+pub fn track_escalations(metrics: &Arc<Mutex<Metrics>>) {
+    let mut guard = match metrics.lock() {
+        Ok(output) => output,
+        Err(err) => error(&format!("locking value [{}]", err)),
+    };
+    guard.total_stage_escalations.get_or_insert(0);
+}
+
 /// Thread-safe counter increment for runtime metrics.
 /// Locks the mutex and increments the specified counter by 1.
 /// Terminates application on lock poisoning (unrecoverable error).
@@ -78,5 +131,72 @@ pub fn add_one(metrics: &Arc<Mutex<Metrics>>, key: MetricEvent) {
         MetricEvent::Failures => guard.total_failures += 1,
         MetricEvent::NormalLoopTimes => guard.total_normal_loop_times += 1,
         MetricEvent::EmergencyLoopTimes => guard.total_emergency_loop_times += 1,
+        MetricEvent::StageEscalations => *guard.total_stage_escalations.get_or_insert(0) += 1,
+    }
+}
+
+/// Outcome of a single monitoring cycle, stamped with a monotonic clock.
+#[derive(Debug, Clone, Copy)]
+pub struct CycleSample {
+    pub at: Instant,
+    pub ok: bool,
+    pub succeeds: u64,
+    pub failures: u64,
+}
+
+/// Time-stamped ring buffer of recent cycle results used by the availability policy.
+/// Samples older than `window` are dropped as new ones arrive.
+#[derive(Debug, Clone)]
+pub struct CycleHistory {
+    window: Duration,
+    started: Instant,
+    samples: VecDeque<CycleSample>,
+}
+
+impl CycleHistory {
+    pub fn new(window: Duration, started: Instant) -> Self {
+        Self { window, started, samples: VecDeque::new() }
+    }
+
+    /// Appends a sample and evicts everything that fell out of the window.
+    pub fn record(&mut self, sample: CycleSample) {
+        let now = sample.at;
+        self.samples.push_back(sample);
+        while let Some(front) = self.samples.front() {
+            if now.saturating_duration_since(front.at) > self.window {
+                self.samples.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize { self.samples.len() }
+
+    pub fn is_empty(&self) -> bool { self.samples.is_empty() }
+
+    pub fn window_secs(&self) -> u64 { self.window.as_secs() }
+
+    /// True once monitoring has been running for at least one full window,
+    /// so early samples cannot trip the policy on their own.
+    pub fn covers_window(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.started) >= self.window
+    }
+
+    /// Availability in percent over the retained samples, or `None` when empty.
+    pub fn availability(&self, basis: AvailabilityBasis) -> Option<f64> {
+        let (good, total) = match basis {
+            AvailabilityBasis::Cycles => (
+                self.samples.iter().filter(|s| s.ok).count() as u64,
+                self.samples.len() as u64,
+            ),
+            AvailabilityBasis::Probes => self.samples.iter().fold((0, 0), |(good, total), s| {
+                (good + s.succeeds, total + s.succeeds + s.failures)
+            }),
+        };
+        if total == 0 {
+            return None;
+        }
+        Some(good as f64 * 100.0 / total as f64)
     }
 }
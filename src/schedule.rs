@@ -0,0 +1,133 @@
+use std::fmt;
+use std::num::NonZeroU32;
+use std::time::Duration;
+use chrono::{Datelike, Local, Timelike, Weekday};
+use serde::Deserialize;
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+/// Raw weekday/time-range entry as written in the configuration file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TimeWindowSpec {
+    /// Three-letter weekday names ("mon".."sun"); empty means every day
+    pub days: Vec<String>,
+    /// Local start time, "HH:MM"
+    pub start: String,
+    /// Local end time, "HH:MM" (exclusive); an end before the start crosses midnight
+    pub end: String,
+}
+
+/// A recurring local-time range such as "Mon-Fri 01:00-05:00".
+///
+/// A window whose end is before its start wraps past midnight and is attributed
+/// to the day it starts on, so `sat 22:00-02:00` also covers early Sunday.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeWindow {
+    pub days: Vec<Weekday>,
+    pub start: u32,
+    pub end: u32,
+}
+
+impl TimeWindow {
+    /// Validates a raw entry; the error names the offending sub-field.
+    pub fn parse(spec: &TimeWindowSpec) -> Result<Self, (&'static str, String)> {
+        let mut days = Vec::with_capacity(spec.days.len());
+        for day in &spec.days {
+            let parsed = day
+                .parse::<Weekday>()
+                .map_err(|_| ("days", format!("'{}' is not a weekday (use mon..sun)", day)))?;
+            days.push(parsed);
+        }
+        let start = parse_clock(&spec.start).ok_or_else(|| ("start", format!("'{}' is not a HH:MM time", spec.start)))?;
+        let end = parse_clock(&spec.end).ok_or_else(|| ("end", format!("'{}' is not a HH:MM time", spec.end)))?;
+        if start == MINUTES_PER_DAY {
+            return Err(("start", "a window cannot start at 24:00".to_string()));
+        }
+        if start == end {
+            return Err(("end", "window start and end must differ".to_string()));
+        }
+        Ok(Self { days, start, end })
+    }
+
+    /// True when `minute` (minutes since local midnight) on `weekday` falls inside the window.
+    pub fn contains(&self, weekday: Weekday, minute: u32) -> bool {
+        if self.start < self.end {
+            return self.applies_on(weekday) && minute >= self.start && minute < self.end;
+        }
+        (self.applies_on(weekday) && minute >= self.start) || (self.applies_on(weekday.pred()) && minute < self.end)
+    }
+
+    /// Evaluates the window against the local wall clock.
+    pub fn contains_now(&self) -> bool {
+        let now = Local::now();
+        self.contains(now.weekday(), now.hour() * 60 + now.minute())
+    }
+
+    fn applies_on(&self, weekday: Weekday) -> bool {
+        self.days.is_empty() || self.days.contains(&weekday)
+    }
+}
+
+impl fmt::Display for TimeWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.days.is_empty() {
+            let days: Vec<String> = self.days.iter().map(|d| d.to_string().to_lowercase()).collect();
+            write!(f, "{} ", days.join(","))?;
+        }
+        write!(
+            f,
+            "{:02}:{:02}-{:02}:{:02}",
+            self.start / 60,
+            self.start % 60,
+            self.end / 60,
+            self.end % 60
+        )
+    }
+}
+
+/// Raw policy profile as written in the configuration file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ProfileSpec {
+    pub name: String,
+    #[serde(flatten)]
+    pub window: TimeWindowSpec,
+    #[serde(alias = "secs-for-normal-loop")]
+    pub normal_secs: Option<u64>,
+    #[serde(alias = "secs-for-emergency-loop")]
+    pub emergency_secs: Option<u64>,
+    #[serde(alias = "times-for-emergency-loop")]
+    pub emergency_retries: Option<u32>,
+    pub strict: Option<bool>,
+}
+
+/// Named overrides applied while the local time falls inside `window`.
+/// Unset values keep the base configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyProfile {
+    pub name: String,
+    pub window: TimeWindow,
+    pub normal_interval: Option<Duration>,
+    pub emergency_interval: Option<Duration>,
+    pub emergency_retries: Option<NonZeroU32>,
+    pub strict: Option<bool>,
+}
+
+/// First profile whose window contains the current local time.
+pub fn active_profile(profiles: &[PolicyProfile]) -> Option<&PolicyProfile> {
+    profiles.iter().find(|profile| profile.window.contains_now())
+}
+
+fn parse_clock(value: &str) -> Option<u32> {
+    let (hours, minutes) = value.trim().split_once(':')?;
+    let hours: u32 = hours.parse().ok()?;
+    let minutes: u32 = minutes.parse().ok()?;
+    // Range-check before the arithmetic so huge hours cannot overflow
+    if minutes >= 60 || hours > 24 {
+        return None;
+    }
+    let total = hours * 60 + minutes;
+    // "24:00" is accepted as the end of the day
+    (total <= MINUTES_PER_DAY).then_some(total)
+}
@@ -5,10 +5,11 @@ use crossbeam_channel::{select, tick};
 
 use crate::runtime::Metrics;
 use crate::system::error;
+use crate::template::Templates;
 
 /// Install Ctrl-C handler and background watcher that flushes runtime metrics
-/// and exits the process gracefully.
-pub fn install_ctrlc_handler(ctrlc_flag: Arc<AtomicBool>, metrics: Arc<Mutex<Metrics>>) {
+/// and exits the process gracefully, printing the summary with `templates`.
+pub fn install_ctrlc_handler(ctrlc_flag: Arc<AtomicBool>, metrics: Arc<Mutex<Metrics>>, templates: Templates) {
     let ctrlc_clone = ctrlc_flag.clone();
     let metrics_clone = Arc::clone(&metrics);
 
@@ -26,7 +27,7 @@ pub fn install_ctrlc_handler(ctrlc_flag: Arc<AtomicBool>, metrics: Arc<Mutex<Met
                         println!("Writing final results and exiting...");
                         let output = metrics_clone.lock()
                             .unwrap_or_else(|err| error(&format!("locking runtime info: {}", err)));
-                        println!("{}", output.summary_string());
+                        println!("{}", output.summary(&templates));
                         output.write();
                         std::process::exit(0);
                     }
@@ -0,0 +1,248 @@
+use std::time::{Duration, Instant};
+
+use crate::config::{DecisionPolicy, MonitorConfig, MAX_COUNTDOWN_SECS};
+
+/// Where the monitor stands after a cycle.
+///
+/// Retry semantics, with `F = fail_threshold`, `R = recover_threshold` and
+/// `N = emergency_retries`:
+///
+/// * `Normal` -> `Suspect` on the first failed cycle, or straight to `Emergency` when `F == 1`.
+/// * `Suspect` -> `Emergency` on the `F`-th consecutive failed cycle; any success returns to `Normal`.
+/// * `Emergency` allows exactly `N` further checks: each failed check consumes one retry,
+///   and the failed check that uses up the last one moves to `Acting`. The action therefore
+///   fires after `F + N` failed checks in total.
+/// * With `max_outage` set the retry count is ignored: a failed check in `Emergency` moves
+///   to `Acting` once the countdown has run for `max_outage` on the monotonic clock.
+/// * With a `countdown` configured, a due action first moves to `Countdown`. Probing continues;
+///   the first failed check at or after the deadline moves to `Acting`, and any successful check
+///   cancels the countdown as if the action had run (see `Acting` below).
+/// * Successful checks in `Emergency` never consume retries; `R` consecutive successes move
+///   to `Recovered`, and a failure in between resets that streak but not the countdown.
+/// * Under the availability policy `F`, `N` and `max_outage` play no part: failed cycles stay
+///   in `Suspect` until the driver reports availability below the threshold, and a failed check
+///   while it is low enters `Emergency` with the action due at once. In `Emergency` the action
+///   falls due again on any failed check while availability stays low.
+/// * `Recovered` is reported for a single cycle and then behaves like `Normal`.
+/// * From `Acting` (when the driver keeps running, e.g. after a held or failed action) a failed
+///   check starts a fresh countdown and a success counts towards recovery.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonitorState {
+    Normal,
+    Suspect {
+        failed: u32,
+        since: Instant,
+    },
+    Emergency {
+        /// Failed cycles since the outage began
+        failed: u32,
+        /// Consecutive successful cycles towards recovery
+        recovered: u32,
+        retries_left: u32,
+        /// Start of the outage
+        since: Instant,
+        /// Start of the current countdown, reset when an action is held back
+        countdown_from: Instant,
+    },
+    /// The action is due and scheduled for `deadline`
+    Countdown {
+        failed: u32,
+        since: Instant,
+        deadline: Instant,
+    },
+    Acting {
+        failed: u32,
+        since: Instant,
+    },
+    Recovered {
+        outage: Duration,
+    },
+}
+
+/// Why an action that is due must not run yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hold {
+    /// Skip the action and start a fresh countdown
+    Restart,
+    /// Keep the countdown exhausted and re-check on the next failed cycle
+    Wait,
+}
+
+/// Result of one probing cycle fed to [`transition`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CycleInput {
+    pub ok: bool,
+    pub at: Instant,
+    /// Set by the driver when the action must not fire right now
+    pub hold: Option<Hold>,
+    /// Set by the driver under the availability policy while availability over a full
+    /// window is below the threshold
+    pub availability_low: bool,
+}
+
+/// The thresholds the state machine needs, taken from [`MonitorConfig`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatePolicy {
+    pub fail_threshold: u32,
+    pub recover_threshold: u32,
+    pub emergency_retries: u32,
+    pub max_outage: Option<Duration>,
+    /// Delay between the action falling due and running it
+    pub countdown: Option<Duration>,
+    pub decision: DecisionPolicy,
+}
+
+impl From<&MonitorConfig> for StatePolicy {
+    fn from(cfg: &MonitorConfig) -> Self {
+        Self {
+            fail_threshold: cfg.fail_threshold_cycles(),
+            recover_threshold: cfg.recover_threshold_cycles(),
+            emergency_retries: cfg.emergency_retry_attempts(),
+            max_outage: cfg.max_outage,
+            countdown: cfg.countdown,
+            decision: cfg.policy,
+        }
+    }
+}
+
+/// Outcome of a transition: the next state and, when an action was due but held, which hold applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    pub state: MonitorState,
+    pub held: Option<Hold>,
+}
+
+impl MonitorState {
+    /// True while the emergency countdown is running or the action is due.
+    pub fn is_emergency(&self) -> bool {
+        matches!(self, MonitorState::Emergency { .. } | MonitorState::Countdown { .. } | MonitorState::Acting { .. })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MonitorState::Normal => "normal",
+            MonitorState::Suspect { .. } => "suspect",
+            MonitorState::Emergency { .. } => "emergency",
+            MonitorState::Countdown { .. } => "countdown",
+            MonitorState::Acting { .. } => "acting",
+            MonitorState::Recovered { .. } => "recovered",
+        }
+    }
+}
+
+/// Pure transition function: no I/O, no sleeping, time comes from `input.at`.
+pub fn transition(state: MonitorState, input: CycleInput, policy: &StatePolicy) -> Step {
+    let now = input.at;
+    let fail_threshold = policy.fail_threshold.max(1);
+    let recover_threshold = policy.recover_threshold.max(1);
+    let retries = policy.emergency_retries.max(1);
+
+    let state = match (state, input.ok) {
+        (MonitorState::Normal | MonitorState::Recovered { .. }, true) => MonitorState::Normal,
+        (MonitorState::Normal | MonitorState::Recovered { .. }, false) => {
+            return enter_or_suspect(1, now, fail_threshold, retries, policy, input);
+        }
+        (MonitorState::Suspect { .. }, true) => MonitorState::Normal,
+        (MonitorState::Suspect { failed, since }, false) => {
+            return enter_or_suspect(failed + 1, since, fail_threshold, retries, policy, input);
+        }
+        (MonitorState::Emergency { failed, recovered, retries_left, since, countdown_from }, true) => {
+            if recovered + 1 >= recover_threshold {
+                MonitorState::Recovered { outage: now.saturating_duration_since(since) }
+            } else {
+                MonitorState::Emergency { failed, recovered: recovered + 1, retries_left, since, countdown_from }
+            }
+        }
+        (MonitorState::Emergency { failed, retries_left, since, countdown_from, .. }, false) => {
+            let retries_left = retries_left.saturating_sub(1);
+            let due = match (policy.decision, policy.max_outage) {
+                (DecisionPolicy::Availability, _) => input.availability_low,
+                (DecisionPolicy::Consecutive, Some(max)) => now.saturating_duration_since(countdown_from) >= max,
+                (DecisionPolicy::Consecutive, None) => retries_left == 0,
+            };
+            let next = MonitorState::Emergency { failed: failed + 1, recovered: 0, retries_left, since, countdown_from };
+            if due {
+                return act_or_hold(next, now, retries, policy.countdown, input.hold);
+            }
+            next
+        }
+        (MonitorState::Countdown { failed, since, deadline }, false) => {
+            let failed = failed + 1;
+            match input.hold {
+                _ if now < deadline => MonitorState::Countdown { failed, since, deadline },
+                None => MonitorState::Acting { failed, since },
+                Some(Hold::Wait) => {
+                    return Step { state: MonitorState::Countdown { failed, since, deadline }, held: input.hold };
+                }
+                Some(Hold::Restart) => {
+                    let state = MonitorState::Emergency { failed, recovered: 0, retries_left: retries, since, countdown_from: now };
+                    return Step { state, held: input.hold };
+                }
+            }
+        }
+        (MonitorState::Acting { failed, since } | MonitorState::Countdown { failed, since, .. }, true) => {
+            if recover_threshold <= 1 {
+                MonitorState::Recovered { outage: now.saturating_duration_since(since) }
+            } else {
+                MonitorState::Emergency { failed, recovered: 1, retries_left: retries, since, countdown_from: now }
+            }
+        }
+        (MonitorState::Acting { failed, since }, false) => {
+            MonitorState::Emergency { failed: failed + 1, recovered: 0, retries_left: retries, since, countdown_from: now }
+        }
+    };
+    Step { state, held: None }
+}
+
+fn enter_or_suspect(
+    failed: u32,
+    since: Instant,
+    fail_threshold: u32,
+    retries: u32,
+    policy: &StatePolicy,
+    input: CycleInput,
+) -> Step {
+    let now = input.at;
+    let escalate = match policy.decision {
+        DecisionPolicy::Consecutive => failed >= fail_threshold,
+        DecisionPolicy::Availability => input.availability_low,
+    };
+    if !escalate {
+        return Step { state: MonitorState::Suspect { failed, since }, held: None };
+    }
+    let state = MonitorState::Emergency { failed, recovered: 0, retries_left: retries, since, countdown_from: since };
+    // Low availability makes the action due on entry, as does an outage that already
+    // outlasted max_outage before escalating
+    let due = match (policy.decision, policy.max_outage) {
+        (DecisionPolicy::Availability, _) => true,
+        (DecisionPolicy::Consecutive, Some(max)) => now.saturating_duration_since(since) >= max,
+        (DecisionPolicy::Consecutive, None) => false,
+    };
+    if due {
+        return act_or_hold(state, now, retries, policy.countdown, input.hold);
+    }
+    Step { state, held: None }
+}
+
+fn act_or_hold(state: MonitorState, now: Instant, retries: u32, countdown: Option<Duration>, hold: Option<Hold>) -> Step {
+    let MonitorState::Emergency { failed, since, countdown_from, .. } = state else {
+        return Step { state, held: None };
+    };
+    match hold {
+        // The config caps the countdown; the clamp only keeps the deadline from overflowing
+        None => match countdown.and_then(|delay| now.checked_add(delay.min(Duration::from_secs(MAX_COUNTDOWN_SECS)))) {
+            Some(deadline) if deadline > now => {
+                Step { state: MonitorState::Countdown { failed, since, deadline }, held: None }
+            }
+            _ => Step { state: MonitorState::Acting { failed, since }, held: None },
+        },
+        Some(Hold::Restart) => Step {
+            state: MonitorState::Emergency { failed, recovered: 0, retries_left: retries, since, countdown_from: now },
+            held: hold,
+        },
+        Some(Hold::Wait) => Step {
+            state: MonitorState::Emergency { failed, recovered: 0, retries_left: 0, since, countdown_from },
+            held: hold,
+        },
+    }
+}
@@ -1,16 +1,80 @@
-use std::io;
-use std::process::{Command, Output};
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process::{Child, ChildStderr, Command, Output, Stdio};
+use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use colored::Colorize;
 
+use crate::action::{ActionAttempt, ActionKind, ActionResult, DEFAULT_ACTION_TIMEOUT_SECS};
+#[cfg(unix)]
+use crate::logind;
+
 /// Platform abstraction for shell execution, ping command construction,
 /// shutdown flows, and console tweaks.
 pub trait System {
     fn run_shell_command(&self, command: &str, message: Option<&str>) -> io::Result<Output>;
     fn shutdown(&self);
+    /// Runs one emergency action. Platforms that can enforce `timeout` abandon the action
+    /// after it; the default powers off through [`System::shutdown`] and runs commands to completion.
+    fn run_action(&self, action: &ActionKind, _timeout: Duration) -> ActionResult {
+        match action {
+            ActionKind::Poweroff => {
+                self.shutdown();
+                ActionResult::Ok
+            }
+            ActionKind::RunCommand(command) => match self.run_shell_command(command, None) {
+                Ok(output) if output.status.success() => ActionResult::Ok,
+                Ok(output) => ActionResult::Failed(failure_reason(output.status, &output.stderr)),
+                Err(err) => ActionResult::Failed(err.to_string()),
+            },
+            ActionKind::LogOnly => ActionResult::Ok,
+            other => ActionResult::Failed(format!("{} is not supported on this platform", other.as_str())),
+        }
+    }
+    /// Like [`System::run_action`], also returning every command tried on the way. The default
+    /// reports the whole action as a single attempt.
+    fn run_action_recorded(&self, action: &ActionKind, timeout: Duration) -> (ActionResult, Vec<ActionAttempt>) {
+        let started = Instant::now();
+        let result = self.run_action(action, timeout);
+        let commands = self.action_commands(action);
+        let command = if commands.is_empty() { action.as_str().to_string() } else { commands.join(" || ") };
+        let attempt =
+            ActionAttempt { command, result: result.clone(), status: None, stderr: String::new(), elapsed: started.elapsed() };
+        (result, vec![attempt])
+    }
+    /// Shell commands `run_action` would execute for `action`, tried in order until one succeeds.
+    fn action_commands(&self, action: &ActionKind) -> Vec<String> {
+        match action {
+            ActionKind::RunCommand(command) => vec![command.clone()],
+            _ => Vec::new(),
+        }
+    }
+    /// Runs a hook script with extra environment variables and optional stdin, killing it once
+    /// `timeout` passes. Its output goes to pingdown's own stdout.
+    fn run_hook(&self, command: &str, env: &[(String, String)], stdin: Option<&str>, timeout: Duration) -> ActionResult {
+        let mut command = shell(command);
+        command.envs(env.iter().map(|(key, value)| (key, value)));
+        wait_with_timeout(&mut command, stdin, timeout)
+    }
     fn console_setup(&self);
+    /// Reads a state file written by [`System::write_file`].
+    fn read_file(&self, path: &Path) -> io::Result<String> {
+        std::fs::read_to_string(path)
+    }
+    /// Writes a report or state file.
+    fn write_file(&self, path: &Path, contents: &str) -> io::Result<()> {
+        std::fs::write(path, contents)
+    }
     fn build_ping_command(&self, ip: &str) -> String;
+    /// Time since the system booted, where the platform exposes it.
+    fn uptime(&self) -> Option<Duration> { None }
+    /// Address of the default IPv4 gateway, used for the `@gateway` target.
+    fn default_gateway(&self) -> Option<String> { None }
+    /// Sends a message to every logged-in user, where the platform supports it.
+    fn broadcast(&self, _message: &str) {}
+    /// Whether the process may run the shutdown action, where the platform can tell.
+    fn shutdown_privilege(&self) -> Option<bool> { None }
     fn sleep_secs(&self, secs: u64) {
         thread::sleep(Duration::from_secs(secs));
     }
@@ -33,21 +97,72 @@ impl System for DefaultSystem {
     }
 
     fn shutdown(&self) {
-        let _ = self.run_shell_command("shutdown -h now", Some("Starting shutdown..."));
-        self.sleep_secs(7);
-        let _ = self.run_shell_command("poweroff", None);
-        self.sleep_secs(7);
-        let _ = self.run_shell_command("poweroff -f", None);
-        self.sleep_secs(7);
-        let _ = self.run_shell_command("halt", None);
-        self.sleep_secs(7);
-        let _ = self.run_shell_command("init 0", None);
-        self.sleep_secs(7);
-        let _ = self.run_shell_command("systemctl poweroff", None);
+        println!("Starting shutdown...");
+        let (result, attempts) =
+            self.run_action_recorded(&ActionKind::Poweroff, Duration::from_secs(DEFAULT_ACTION_TIMEOUT_SECS));
+        report_shutdown_failure(&result, &attempts);
     }
 
     fn console_setup(&self) { /* no-op on Unix */ }
 
+    fn run_action(&self, action: &ActionKind, timeout: Duration) -> ActionResult {
+        self.run_action_recorded(action, timeout).0
+    }
+
+    /// Power actions go through logind when it answers, otherwise through the shell commands.
+    fn run_action_recorded(&self, action: &ActionKind, timeout: Duration) -> (ActionResult, Vec<ActionAttempt>) {
+        match logind::power_action(action, timeout) {
+            Some(recorded) => recorded,
+            None => run_first_success(&shell_action_commands(action), timeout),
+        }
+    }
+
+    fn action_commands(&self, action: &ActionKind) -> Vec<String> {
+        logind::command_line(action).into_iter().chain(shell_action_commands(action)).collect()
+    }
+
+    fn uptime(&self) -> Option<Duration> {
+        // First field of /proc/uptime is seconds since boot (Linux only)
+        let contents = std::fs::read_to_string("/proc/uptime").ok()?;
+        let secs: f64 = contents.split_whitespace().next()?.parse().ok()?;
+        Some(Duration::from_secs_f64(secs))
+    }
+
+    fn broadcast(&self, message: &str) {
+        // wall reads the message from stdin, which avoids quoting it for the shell
+        let child = Command::new("wall").stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::null()).spawn();
+        if let Ok(mut child) = child {
+            if let Some(mut stdin) = child.stdin.take() {
+                let _ = io::Write::write_all(&mut stdin, message.as_bytes());
+            }
+            let _ = child.wait();
+        }
+    }
+
+    fn shutdown_privilege(&self) -> Option<bool> {
+        let output = self.run_shell_command("id -u", None).ok()?;
+        Some(String::from_utf8_lossy(&output.stdout).trim() == "0")
+    }
+
+    fn default_gateway(&self) -> Option<String> {
+        // Linux: the default route in /proc/net/route holds the gateway as little-endian hex
+        if let Ok(routes) = std::fs::read_to_string("/proc/net/route") {
+            for line in routes.lines().skip(1) {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                if fields.len() > 2 && fields[1] == "00000000" {
+                    let raw = u32::from_str_radix(fields[2], 16).ok()?;
+                    return Some(std::net::Ipv4Addr::from(raw.to_le_bytes()).to_string());
+                }
+            }
+            return None;
+        }
+        // BSD/macOS: "gateway: 192.168.1.1" in the route lookup output
+        let output = self.run_shell_command("route -n get default", None).ok()?;
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("gateway:").map(|gw| gw.trim().to_string()))
+    }
+
     fn build_ping_command(&self, ip: &str) -> String {
         format!("ping -c 1 {}", ip)
     }
@@ -61,18 +176,238 @@ impl System for DefaultSystem {
     }
 
     fn shutdown(&self) {
-        let _ = self.run_shell_command("shutdown /s /t 0", Some("Starting shutdown..."));
+        println!("Starting shutdown...");
+        let (result, attempts) =
+            self.run_action_recorded(&ActionKind::Poweroff, Duration::from_secs(DEFAULT_ACTION_TIMEOUT_SECS));
+        report_shutdown_failure(&result, &attempts);
     }
 
     fn console_setup(&self) {
         let _ = self.run_shell_command("chcp 65001", None);
     }
 
+    fn run_action(&self, action: &ActionKind, timeout: Duration) -> ActionResult {
+        self.run_action_recorded(action, timeout).0
+    }
+
+    fn run_action_recorded(&self, action: &ActionKind, timeout: Duration) -> (ActionResult, Vec<ActionAttempt>) {
+        run_first_success(&self.action_commands(action), timeout)
+    }
+
+    fn action_commands(&self, action: &ActionKind) -> Vec<String> {
+        let command = match action {
+            ActionKind::Poweroff => "shutdown /s /t 0",
+            ActionKind::Reboot => "shutdown /r /t 0",
+            ActionKind::Suspend => "rundll32.exe powrprof.dll,SetSuspendState 0,1,0",
+            ActionKind::Hibernate => "shutdown /h",
+            ActionKind::RunCommand(command) => command,
+            ActionKind::LogOnly => return Vec::new(),
+        };
+        vec![command.to_string()]
+    }
+
+    fn broadcast(&self, message: &str) {
+        let _ = Command::new("msg").arg("*").arg(message).output();
+    }
+
+    fn shutdown_privilege(&self) -> Option<bool> {
+        // `net session` only succeeds from an elevated prompt
+        let output = self.run_shell_command("net session", None).ok()?;
+        Some(output.status.success())
+    }
+
+    fn default_gateway(&self) -> Option<String> {
+        // Row format: Network Destination, Netmask, Gateway, Interface, Metric
+        let output = self.run_shell_command("route print -4 0.0.0.0", None).ok()?;
+        String::from_utf8_lossy(&output.stdout).lines().find_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            (fields.len() >= 5 && fields[0] == "0.0.0.0" && fields[1] == "0.0.0.0").then(|| fields[2].to_string())
+        })
+    }
+
     fn build_ping_command(&self, ip: &str) -> String {
         format!("ping -n 1 {}", ip)
     }
 }
 
+/// Shell fallback chain for when logind cannot be reached.
+#[cfg(unix)]
+fn shell_action_commands(action: &ActionKind) -> Vec<String> {
+    let commands: &[&str] = match action {
+        ActionKind::Poweroff => &["shutdown -h now", "systemctl poweroff", "poweroff", "halt -p", "init 0"],
+        ActionKind::Reboot => &["shutdown -r now", "systemctl reboot", "reboot"],
+        ActionKind::Suspend => &["systemctl suspend", "pm-suspend", "pmset sleepnow"],
+        ActionKind::Hibernate => &["systemctl hibernate", "pm-hibernate"],
+        ActionKind::RunCommand(command) => return vec![command.clone()],
+        ActionKind::LogOnly => &[],
+    };
+    commands.iter().map(|command| command.to_string()).collect()
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+/// Runs a shell command, killing it once `timeout` passes.
+fn run_with_timeout(command: &str, timeout: Duration) -> ActionAttempt {
+    run_attempt(shell(command).stdout(Stdio::null()), command, None, timeout)
+}
+
+/// Spawns a prepared command and waits for it, killing it once `timeout` passes.
+pub(crate) fn wait_with_timeout(command: &mut Command, stdin: Option<&str>, timeout: Duration) -> ActionResult {
+    run_attempt(command, "", stdin, timeout).result
+}
+
+/// Like [`wait_with_timeout`], also keeping the exit status, stderr and run time under `label`.
+/// The command runs in its own process group, and a timeout kills the whole group.
+pub(crate) fn run_attempt(command: &mut Command, label: &str, stdin: Option<&str>, timeout: Duration) -> ActionAttempt {
+    let started = Instant::now();
+    let attempt = |result: ActionResult, status: Option<String>, stderr: &[u8]| ActionAttempt {
+        command: label.to_string(),
+        result,
+        status,
+        stderr: String::from_utf8_lossy(stderr).trim().to_string(),
+        elapsed: started.elapsed(),
+    };
+    let input = if stdin.is_some() { Stdio::piped() } else { Stdio::null() };
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(command, 0);
+    let mut child = match command.stdin(input).stderr(Stdio::piped()).spawn() {
+        Ok(child) => child,
+        Err(err) => return attempt(ActionResult::Failed(err.to_string()), None, &[]),
+    };
+    let stderr = StderrDrain::start(child.stderr.take());
+    if let (Some(data), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        // A script that ignores its input may exit before reading it
+        let _ = pipe.write_all(data.as_bytes());
+    }
+    let deadline = started + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                let stderr = stderr.finish();
+                let result =
+                    if status.success() { ActionResult::Ok } else { ActionResult::Failed(failure_reason(status, &stderr)) };
+                return attempt(result, Some(status.to_string()), &stderr);
+            }
+            Ok(None) if Instant::now() >= deadline => {
+                kill_group(&mut child);
+                let _ = child.wait();
+                // Whatever it printed before hanging is often the best clue
+                return attempt(ActionResult::TimedOut, None, &stderr.finish());
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(50)),
+            Err(err) => return attempt(ActionResult::Failed(err.to_string()), None, &[]),
+        }
+    }
+}
+
+/// Kills the process group `child` leads, so whatever it started goes too.
+#[cfg(unix)]
+fn kill_group(child: &mut Child) {
+    // SAFETY: kill(2) takes no pointers; a negative pid addresses the group
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    let _ = child.kill();
+}
+
+#[cfg(windows)]
+fn kill_group(child: &mut Child) {
+    let _ = child.kill();
+}
+
+/// Reads a child's stderr on its own thread, so a command writing more than the pipe
+/// holds is not blocked while we wait for it. Keeps the first [`STDERR_LIMIT`] bytes.
+struct StderrDrain {
+    data: Arc<Mutex<Vec<u8>>>,
+    reader: Option<thread::JoinHandle<()>>,
+}
+
+const STDERR_LIMIT: usize = 64 * 1024;
+
+impl StderrDrain {
+    fn start(pipe: Option<ChildStderr>) -> Self {
+        let data = Arc::new(Mutex::new(Vec::new()));
+        let reader = pipe.map(|mut pipe| {
+            let data = Arc::clone(&data);
+            thread::spawn(move || {
+                let mut buf = [0u8; 8192];
+                while let Ok(read @ 1..) = pipe.read(&mut buf) {
+                    let mut data = data.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                    let room = STDERR_LIMIT.saturating_sub(data.len());
+                    data.extend_from_slice(&buf[..read.min(room)]);
+                }
+            })
+        });
+        Self { data, reader }
+    }
+
+    /// What was read so far, once the pipe closes or after a short grace period, since
+    /// a background process the command left behind may hold it open.
+    fn finish(self) -> Vec<u8> {
+        if let Some(reader) = self.reader {
+            let grace = Instant::now() + Duration::from_millis(500);
+            while !reader.is_finished() && Instant::now() < grace {
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+        let data = self.data.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        data.clone()
+    }
+}
+
+/// Tries each fallback command in turn within one shared deadline.
+fn run_first_success(commands: &[String], timeout: Duration) -> (ActionResult, Vec<ActionAttempt>) {
+    let deadline = Instant::now() + timeout;
+    // Log-only has no commands and always succeeds
+    let mut last = ActionResult::Ok;
+    let mut attempts = Vec::with_capacity(commands.len());
+    for command in commands {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return (ActionResult::TimedOut, attempts);
+        }
+        let attempt = run_with_timeout(command, left);
+        let result = attempt.result.clone();
+        attempts.push(attempt);
+        match result {
+            ActionResult::Failed(reason) => last = ActionResult::Failed(format!("'{}': {}", command, reason)),
+            other => return (other, attempts),
+        }
+    }
+    (last, attempts)
+}
+
+/// Shutdown has nobody to return its result to, so a failure is spelled out on the console.
+fn report_shutdown_failure(result: &ActionResult, attempts: &[ActionAttempt]) {
+    if *result == ActionResult::Ok {
+        return;
+    }
+    println!("{} Shutdown did not go through:", "[ACTION]".bold().red());
+    for attempt in attempts {
+        println!("{}   tried {}", "[ACTION]".bold().red(), attempt);
+    }
+}
+
+fn failure_reason(status: std::process::ExitStatus, stderr: &[u8]) -> String {
+    let stderr = String::from_utf8_lossy(stderr);
+    match stderr.lines().map(str::trim).find(|line| !line.is_empty()) {
+        Some(line) => format!("{} ({})", status, line),
+        None => status.to_string(),
+    }
+}
+
 /// Terminates program after critical errors with diagnostic information
 pub fn error(message: &str) -> ! {
     eprintln!("\nAn {} occurred during {}\nif it's {} your fault, please contact {} or new an issue on https://www.github.com/H-Chris233/pingdown",
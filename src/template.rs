@@ -0,0 +1,188 @@
+//! Message templates for the console lines, wall messages and alert mails, rendered with
+//! minijinja. Every template has a built-in default that produces the stock output, so only
+//! the ones a user overrides change anything.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+use colored::{Color, Colorize};
+use minijinja::value::Rest;
+use minijinja::{Environment, Error, ErrorKind, Value};
+
+/// Built-in templates, by name. The variables each one receives are listed in the README.
+const DEFAULTS: &[(&str, &str)] = &[
+    (
+        "start",
+        r#"{{ "[NORMAL]" | style("bold", "green") }} {{ interval }}sec loop{% if policy == "availability" %}, availability policy ({{ threshold }}% over {{ window }}s){% endif %}..."#,
+    ),
+    (
+        "normal",
+        r#"{{ "[NORMAL]" | style("bold", "green") }} {{ "OK" | style("bold", "green") }} | up: {{ up }} | down: {{ down }} | next: {{ next }}s"#,
+    ),
+    (
+        "flapping",
+        r#"{{ "[NORMAL]" | style("bold", "yellow") }} {{ "FLAPPING" | style("bold", "yellow") }} | up: {{ up }} | down: {{ down }} | transitions: {{ transitions }}/{{ flap_window }}s | next: {{ next }}s"#,
+    ),
+    (
+        "degraded",
+        r#"{{ "[NORMAL]" | style("bold", "yellow") }} {{ "DEGRADED" | style("bold", "yellow") }} | up: {{ up }} | down: {{ down }} | failed cycles: {{ failed }}/{{ fail_threshold }}{% if root_cause %} | root cause: {{ root_cause }}{% endif %} | next: {{ next }}s"#,
+    ),
+    (
+        "emergency",
+        r#"{{ "[EMERGENCY]" | style("bold", color) }} {{ label | style("bold", color) }} | up: {{ up }} | down: {{ down }} | failed cycles: {{ failed }} | recovered cycles: {{ recovered }}/{{ recover_threshold }}{% if stage %} | stage: {{ stage }}{% endif %}{% if root_cause %} | root cause: {{ root_cause }}{% endif %} | next: {{ next }}s"#,
+    ),
+    (
+        "countdown",
+        r#"{{ "[EMERGENCY]" | style("bold", "red") }} {{ "COUNTDOWN" | style("bold", "red") }} | up: {{ up }} | down: {{ down }} | failed cycles: {{ failed }}{% if stage %} | stage: {{ stage }}{% endif %} | actions in: {{ remaining }}s | next: {{ next }}s"#,
+    ),
+    (
+        "availability",
+        r#"{{ "[NORMAL]" | style("bold", color) }} {{ label | style("bold", color) }} | up: {{ up }} | down: {{ down }} | availability: {{ "%.1f" | format(availability) }}%/{{ threshold }}% over {{ cycles }} cycle(s) | next: {{ next }}s"#,
+    ),
+    (
+        "emergency-enter",
+        r#"{{ "[EMERGENCY]" | style("bold", "red") }} {% if policy == "availability" %}Availability {{ "%.1f" | format(availability) }}% below {{ threshold }}% over the last {{ window }}s.{% else %}Connection lost after {{ failed }} failed cycle(s). Entering emergency loop ({{ interval }}s interval, {% if policy == "max-outage" %}{{ max_outage }}s outage limit{% else %}{{ retries_left }} tries{% endif %}).{% endif %}{% if root_cause %}
+{{ "[EMERGENCY]" | style("bold", "red") }} Root cause: {{ root_cause }} down.{% endif %}"#,
+    ),
+    (
+        "emergency-remaining",
+        r#"{{ "[EMERGENCY]" | style("bold", "red") }} {% if policy == "max-outage" %}{{ remaining }}s remaining (down for {{ outage_secs }}s)...{% else %}{{ retries_left }} tries remaining...{% endif %}"#,
+    ),
+    (
+        "action",
+        r#"{{ "[EMERGENCY]" | style("bold", "red") }} {% if policy == "max-outage" %}Down for {{ outage_secs }}s (limit {{ max_outage }}s). {% elif policy == "retries" %}Exceeded maximum retries. {% endif %}Running {{ actions | length }} action(s)..."#,
+    ),
+    (
+        "recovery",
+        r#"{{ "[EMERGENCY]" | style("bold", "green") }} Reconnected after {{ outage_secs }}s.
+{{ "[EMERGENCY]" | style("bold", "green") }} Exiting {{ interval }}sec emergency loop..."#,
+    ),
+    (
+        "summary",
+        "Summary -> up: {{ up }} | down: {{ down }} | normal_loops: {{ normal_loops }} | emergency_loops: {{ emergency_loops }}{% if escalations is not none %} | escalations: {{ escalations }}{% endif %}",
+    ),
+    (
+        "wall-countdown",
+        r#"pingdown: network connectivity has been down for {{ outage_secs }}s. This machine will run {{ actions | join(" -> ") }} in {{ remaining // 60 }}m{{ "%02d" | format(remaining % 60) }}s. Save your work."#,
+    ),
+    ("wall-cancel", "pingdown: pending shutdown cancelled, {{ reason }}."),
+    (
+        "mail-subject",
+        r#"[pingdown] {{ host }}: {% if event == "emergency" %}connectivity lost{% elif event == "recovery" %}connectivity restored after {{ outage_secs }}s{% else %}running {{ actions | join(", ") }}{% endif %}"#,
+    ),
+    (
+        "mail-body",
+        r#"{% if event == "emergency" %}Connection lost after {{ failed_cycles }} failed cycle(s).{% if retries_left is not none %} {{ retries_left }} tries left before the actions run.{% endif %}
+{% elif event == "recovery" %}Reconnected after an outage of {{ outage_secs }}s.
+{% elif event == "action" %}Down for {{ outage_secs }}s after {{ failed_cycles }} failed cycle(s). Running {{ actions | length }} action(s).
+{% endif %}{% if root_causes %}Root cause: {{ root_causes | join(", ") }} down.
+{% endif %}
+Host:            {{ host }}
+Time:            {{ time }}
+State:           {{ state }}
+Failing targets: {{ failing_targets | join(", ") }}
+{% if outage_start %}Outage start:    {{ outage_start }}
+{% endif %}Outage duration: {{ outage_secs }}s
+Actions:         {{ actions | join(", ") }}
+
+Targets:
+{% for target in targets %}  {{ target.target }} {{ target.status }}{% if target.root_cause %} (via {{ target.root_cause }}){% endif %}
+{% endfor %}"#,
+    ),
+];
+
+/// Names that can be overridden.
+pub fn template_names() -> impl Iterator<Item = &'static str> {
+    DEFAULTS.iter().map(|(name, _)| *name)
+}
+
+/// `{{ text | style("bold", "red") }}` colours console output the way the rest of pingdown does,
+/// and prints plain text where colours are off.
+fn style(value: String, styles: Rest<String>) -> Result<String, Error> {
+    let mut styled = value.normal();
+    for name in styles.iter() {
+        styled = match name.as_str() {
+            "bold" => styled.bold(),
+            "dimmed" => styled.dimmed(),
+            "italic" => styled.italic(),
+            "underline" => styled.underline(),
+            color => match color.parse::<Color>() {
+                Ok(color) => styled.color(color),
+                Err(()) => return Err(Error::new(ErrorKind::InvalidOperation, format!("unknown style '{}'", color))),
+            },
+        };
+    }
+    Ok(styled.to_string())
+}
+
+fn environment() -> Environment<'static> {
+    let mut env = Environment::new();
+    env.add_filter("style", style);
+    for (name, source) in DEFAULTS {
+        env.add_template(name, source).expect("built-in templates are valid");
+    }
+    env
+}
+
+fn builtin() -> &'static Arc<Environment<'static>> {
+    static BUILTIN: OnceLock<Arc<Environment<'static>>> = OnceLock::new();
+    BUILTIN.get_or_init(|| Arc::new(environment()))
+}
+
+/// The built-in templates with the user's overrides on top.
+#[derive(Clone)]
+pub struct Templates {
+    overrides: BTreeMap<String, String>,
+    env: Arc<Environment<'static>>,
+}
+
+impl Templates {
+    /// Compiles the overrides; the error names the offending template.
+    pub fn new(overrides: BTreeMap<String, String>) -> Result<Self, (String, String)> {
+        if overrides.is_empty() {
+            return Ok(Self::default());
+        }
+        let mut env = environment();
+        for (name, source) in &overrides {
+            if !template_names().any(|known| known == name) {
+                let known: Vec<&str> = template_names().collect();
+                return Err((name.clone(), format!("unknown template; expected one of {}", known.join(", "))));
+            }
+            if source.is_empty() {
+                return Err((name.clone(), "a template is required (NAME=TEXT)".to_string()));
+            }
+            env.add_template_owned(name.clone(), source.clone()).map_err(|err| (name.clone(), err.to_string()))?;
+        }
+        Ok(Self { overrides, env: Arc::new(env) })
+    }
+
+    pub fn overrides(&self) -> &BTreeMap<String, String> { &self.overrides }
+
+    /// Renders `name`; an override that fails at runtime is reported and the built-in used instead.
+    pub fn render(&self, name: &str, ctx: Value) -> String {
+        let rendered = self.env.get_template(name).and_then(|template| template.render(&ctx));
+        match rendered {
+            Ok(text) => text,
+            Err(err) => {
+                println!("{} '{}' failed ({}); using the built-in template.", "[TEMPLATE]".bold().yellow(), name, err);
+                builtin().get_template(name).and_then(|template| template.render(&ctx)).unwrap_or_default()
+            }
+        }
+    }
+}
+
+impl Default for Templates {
+    fn default() -> Self { Self { overrides: BTreeMap::new(), env: Arc::clone(builtin()) } }
+}
+
+impl PartialEq for Templates {
+    fn eq(&self, other: &Self) -> bool { self.overrides == other.overrides }
+}
+
+impl Eq for Templates {}
+
+impl fmt::Debug for Templates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Templates").field("overrides", &self.overrides).finish()
+    }
+}
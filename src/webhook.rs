@@ -0,0 +1,155 @@
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_WEBHOOK_TIMEOUT_SECS: u64 = 10;
+pub const DEFAULT_WEBHOOK_RETRIES: u32 = 3;
+/// First pause between delivery attempts; it doubles after each failure
+pub const DEFAULT_WEBHOOK_BACKOFF_SECS: u64 = 2;
+
+/// Raw webhook entry as written in the configuration file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct WebhookSpec {
+    pub url: String,
+    #[serde(alias = "timeout-secs")]
+    pub timeout: Option<u64>,
+    /// Extra attempts after the first one fails
+    pub retries: Option<u32>,
+}
+
+/// An HTTP endpoint that receives every notification as a JSON POST.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Webhook {
+    pub url: String,
+    pub timeout: Duration,
+    pub retries: u32,
+    pub backoff: Duration,
+}
+
+impl Webhook {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            timeout: Duration::from_secs(DEFAULT_WEBHOOK_TIMEOUT_SECS),
+            retries: DEFAULT_WEBHOOK_RETRIES,
+            backoff: Duration::from_secs(DEFAULT_WEBHOOK_BACKOFF_SECS),
+        }
+    }
+
+    /// Validates a raw entry; the error names the offending sub-field.
+    pub fn parse(spec: &WebhookSpec) -> Result<Self, (&'static str, String)> {
+        let url = spec.url.trim();
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(("url", format!("'{}' is not an http:// or https:// URL", url)));
+        }
+        let timeout = match spec.timeout {
+            Some(0) => return Err(("timeout", "webhook timeout must be greater than zero seconds".to_string())),
+            Some(secs) => Duration::from_secs(secs),
+            None => Duration::from_secs(DEFAULT_WEBHOOK_TIMEOUT_SECS),
+        };
+        let retries = spec.retries.unwrap_or(DEFAULT_WEBHOOK_RETRIES);
+        Ok(Self { url: url.to_string(), timeout, retries, backoff: Duration::from_secs(DEFAULT_WEBHOOK_BACKOFF_SECS) })
+    }
+}
+
+/// A delivery that ran out of retries, waiting in the queue file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueuedDelivery {
+    pub url: String,
+    pub timeout_secs: u64,
+    pub body: String,
+}
+
+/// Why a POST failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeliveryError {
+    pub message: String,
+    /// Worth sending again later: the receiver was unreachable, overloaded or failed itself
+    pub retryable: bool,
+}
+
+impl fmt::Display for DeliveryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// POSTs `body` once.
+pub fn post(url: &str, body: &str, timeout: Duration) -> Result<(), DeliveryError> {
+    let agent = ureq::AgentBuilder::new().timeout(timeout).build();
+    let request = agent
+        .post(url)
+        .set("Content-Type", "application/json")
+        .set("User-Agent", concat!("pingdown/", env!("CARGO_PKG_VERSION")));
+    match request.send_string(body) {
+        Ok(_) => Ok(()),
+        Err(ureq::Error::Status(code, response)) => Err(DeliveryError {
+            message: format!("HTTP {} {}", code, response.status_text()),
+            retryable: code >= 500 || code == 408 || code == 429,
+        }),
+        Err(ureq::Error::Transport(err)) => Err(DeliveryError {
+            message: err.to_string(),
+            retryable: !matches!(err.kind(), ureq::ErrorKind::InvalidUrl | ureq::ErrorKind::UnknownScheme),
+        }),
+    }
+}
+
+/// POSTs `body`, retrying with exponential backoff. Returns the last error once every attempt
+/// failed, or the first one that retrying cannot fix.
+pub fn deliver(hook: &Webhook, body: &str) -> Result<(), DeliveryError> {
+    let mut pause = hook.backoff;
+    let mut attempt = 0;
+    loop {
+        match post(&hook.url, body, hook.timeout) {
+            Ok(()) => return Ok(()),
+            Err(err) if attempt >= hook.retries || !err.retryable => return Err(err),
+            Err(_) => {
+                thread::sleep(pause);
+                pause = pause.saturating_mul(2);
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Appends a failed delivery to the queue file.
+pub fn enqueue(path: &Path, hook: &Webhook, body: &str) -> std::io::Result<()> {
+    let entry = QueuedDelivery { url: hook.url.clone(), timeout_secs: hook.timeout.as_secs(), body: body.to_string() };
+    let line = serde_json::to_string(&entry).map_err(std::io::Error::other)?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)
+}
+
+/// Sends the queued deliveries in order, once each, and keeps the ones that may still go
+/// through later. Deliveries the receiver rejected are dropped. Returns how many were sent.
+pub fn flush_queue(path: &Path) -> usize {
+    let Ok(contents) = fs::read_to_string(path) else { return 0 };
+    let mut sent = 0;
+    let mut kept = Vec::new();
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        let Ok(entry) = serde_json::from_str::<QueuedDelivery>(line) else {
+            println!("{} Dropping unreadable queue entry in {}.", "[WEBHOOK]".bold().yellow(), path.display());
+            continue;
+        };
+        match post(&entry.url, &entry.body, Duration::from_secs(entry.timeout_secs.max(1))) {
+            Ok(()) => sent += 1,
+            Err(err) if err.retryable => kept.push(line),
+            Err(err) => println!("{} Dropping queued delivery to {}: {}", "[WEBHOOK]".bold().yellow(), entry.url, err),
+        }
+    }
+    let rest: String = kept.iter().map(|line| format!("{}\n", line)).collect();
+    let written = if rest.is_empty() { fs::remove_file(path) } else { fs::write(path, rest) };
+    if let Err(err) = written {
+        println!("{} Could not update {}: {}", "[WEBHOOK]".bold().red(), path.display(), err);
+    }
+    if sent > 0 {
+        println!("{} Sent {} queued notification(s).", "[WEBHOOK]".bold().green(), sent);
+    }
+    sent
+}
@@ -1,5 +1,8 @@
+#![allow(dead_code)]
+
 use std::collections::HashMap;
 use std::io;
+use std::path::Path;
 use std::process::Output;
 use std::sync::{Arc, Mutex};
 
@@ -10,6 +13,10 @@ pub struct StubSystem {
     // For each IP, a sequence of boolean results to return across calls
     pub responses: Arc<Mutex<HashMap<String, Vec<bool>>>>,
     pub shutdown_calls: Arc<Mutex<u64>>,
+    pub gateway: Option<String>,
+    pub broadcasts: Arc<Mutex<Vec<String>>>,
+    // Report and state files, keyed by path, so tests never touch the working directory
+    pub files: Arc<Mutex<HashMap<String, String>>>,
 }
 
 impl StubSystem {
@@ -20,7 +27,7 @@ impl StubSystem {
         for (ip, ok) in map {
             responses.insert((*ip).to_string(), vec![*ok]);
         }
-        Self { responses: Arc::new(Mutex::new(responses)), shutdown_calls: Arc::new(Mutex::new(0)) }
+        Self { responses: Arc::new(Mutex::new(responses)), ..Self::default() }
     }
 
     pub fn push_sequence(&self, ip: &str, seq: Vec<bool>) {
@@ -29,6 +36,14 @@ impl StubSystem {
     }
 
     pub fn take_shutdowns(&self) -> u64 { *self.shutdown_calls.lock().unwrap() }
+
+    pub fn file(&self, path: &str) -> Option<String> { self.files.lock().unwrap().get(path).cloned() }
+
+    pub fn put_file(&self, path: &str, contents: &str) {
+        self.files.lock().unwrap().insert(path.to_string(), contents.to_string());
+    }
+
+    pub fn take_broadcasts(&self) -> Vec<String> { std::mem::take(&mut *self.broadcasts.lock().unwrap()) }
 }
 
 #[cfg(unix)]
@@ -53,7 +68,12 @@ impl System for StubSystem {
         let ip = command.split_whitespace().last().unwrap_or("").to_string();
         let mut guard = self.responses.lock().unwrap();
         let seq = guard.entry(ip).or_insert_with(|| vec![false]);
-        let result = if seq.is_empty() { false } else { seq.remove(0) };
+        // The last value of a sequence sticks so static responses survive repeated checks
+        let result = match seq.len() {
+            0 => false,
+            1 => seq[0],
+            _ => seq.remove(0),
+        };
         if result {
             make_output("Reply from 1.1.1.1: bytes=32 time=1ms TTL=64")
         } else {
@@ -68,5 +88,19 @@ impl System for StubSystem {
 
     fn console_setup(&self) { /* no-op */ }
 
+    fn default_gateway(&self) -> Option<String> { self.gateway.clone() }
+
+    fn broadcast(&self, message: &str) { self.broadcasts.lock().unwrap().push(message.to_string()); }
+
+    fn read_file(&self, path: &Path) -> io::Result<String> {
+        let files = self.files.lock().unwrap();
+        files.get(&path.display().to_string()).cloned().ok_or_else(|| io::ErrorKind::NotFound.into())
+    }
+
+    fn write_file(&self, path: &Path, contents: &str) -> io::Result<()> {
+        self.files.lock().unwrap().insert(path.display().to_string(), contents.to_string());
+        Ok(())
+    }
+
     fn build_ping_command(&self, ip: &str) -> String { format!("ping -c 1 {}", ip) }
 }
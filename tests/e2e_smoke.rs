@@ -19,8 +19,7 @@ fn cfg(addrs: Vec<&str>, strict: bool, tries: u32) -> MonitorConfig {
         emergency_retries: NonZeroU32::new(tries).unwrap(),
         quiet: true,
         status_only: true,
-        progress: false,
-        verbose: 0,
+        ..MonitorConfig::default()
     }
 }
 
@@ -43,13 +42,239 @@ fn smoke_any_success_and_strict_modes() {
 #[test]
 fn emergency_escalates_and_recovers_without_shutdown() {
     let sys = StubSystem::new();
-    // First attempt fails, second succeeds -> recovery before shutdown
+    // First of two allowed checks fails, the second succeeds -> recovery before shutdown
     sys.push_sequence("down", vec![false, true]);
 
-    let cfg = cfg(vec!["down"], false, 1);
+    let cfg = cfg(vec!["down"], false, 2);
     let metrics = Arc::new(Mutex::new(Metrics::new()));
 
     test_emergency_loop(&cfg, &metrics, &sys);
     // No shutdown should have been invoked
     assert_eq!(sys.take_shutdowns(), 0);
 }
+
+#[test]
+fn emergency_requires_consecutive_successes_to_recover() {
+    let sys = StubSystem::new();
+    // A lucky ping between failures must not end the emergency loop
+    sys.push_sequence("flaky", vec![true, false, true, true]);
+
+    let mut cfg = cfg(vec!["flaky"], false, 2);
+    cfg.recover_threshold = NonZeroU32::new(2).unwrap();
+    let metrics = Arc::new(Mutex::new(Metrics::new()));
+
+    test_emergency_loop(&cfg, &metrics, &sys);
+    assert_eq!(sys.take_shutdowns(), 0);
+    // Every cycle before the final recovering one counts as an emergency loop
+    assert_eq!(metrics.lock().unwrap().total_emergency_loop_times, 3);
+}
+
+#[test]
+fn max_outage_replaces_retry_count() {
+    let sys = StubSystem::new();
+    // Far more failures than --tries would allow; the outage limit has not passed yet
+    sys.push_sequence("slow", vec![false, false, false, true]);
+
+    let mut cfg = cfg(vec!["slow"], false, 1);
+    cfg.max_outage = Some(Duration::from_secs(3600));
+    let metrics = Arc::new(Mutex::new(Metrics::new()));
+
+    test_emergency_loop(&cfg, &metrics, &sys);
+    assert_eq!(sys.take_shutdowns(), 0);
+}
+
+#[test]
+fn startup_grace_holds_back_shutdown() {
+    let sys = StubSystem::new();
+    // Retries run out on the first failure, but the grace period is still running
+    sys.push_sequence("boot", vec![false, false, false, true]);
+
+    let mut cfg = cfg(vec!["boot"], false, 1);
+    cfg.startup_grace = Some(Duration::from_secs(3600));
+    let metrics = Arc::new(Mutex::new(Metrics::new()));
+
+    test_emergency_loop(&cfg, &metrics, &sys);
+    assert_eq!(sys.take_shutdowns(), 0);
+}
+
+#[test]
+fn exhausted_retries_run_the_action_pipeline_and_keep_monitoring() {
+    let sys = StubSystem::new();
+    // Retries run out on the first failure; the link comes back afterwards
+    sys.push_sequence("gone", vec![false, true]);
+
+    let cfg = cfg(vec!["gone"], false, 1);
+    let metrics = Arc::new(Mutex::new(Metrics::new()));
+
+    test_emergency_loop(&cfg, &metrics, &sys);
+    // The default pipeline is a single poweroff
+    assert_eq!(sys.take_shutdowns(), 1);
+    // The attempt is on record before the machine could go down
+    let report = sys.file(pingdown::runtime::METRICS_FILE).expect("report written after the power action");
+    assert!(report.contains("action_log") && report.contains("Poweroff"), "{}", report);
+    assert_eq!(metrics.lock().unwrap().action_log.len(), 1);
+}
+
+#[test]
+fn log_only_pipeline_never_shuts_down() {
+    use pingdown::action::{Action, ActionKind};
+
+    let sys = StubSystem::new();
+    sys.push_sequence("gone", vec![false, true]);
+
+    let mut cfg = cfg(vec!["gone"], false, 1);
+    cfg.actions = vec![Action::new(ActionKind::LogOnly)];
+    let metrics = Arc::new(Mutex::new(Metrics::new()));
+
+    test_emergency_loop(&cfg, &metrics, &sys);
+    assert_eq!(sys.take_shutdowns(), 0);
+}
+
+#[test]
+fn dry_run_reaches_the_action_without_shutting_down() {
+    use pingdown::action::DryRun;
+
+    let sys = StubSystem::new();
+    sys.push_sequence("gone", vec![false, true]);
+
+    let mut cfg = cfg(vec!["gone"], false, 1);
+    cfg.dry_run = DryRun::Continue;
+    let metrics = Arc::new(Mutex::new(Metrics::new()));
+
+    test_emergency_loop(&cfg, &metrics, &sys);
+    assert_eq!(sys.take_shutdowns(), 0);
+}
+
+#[test]
+fn countdown_is_cancelled_when_the_link_returns() {
+    let sys = StubSystem::new();
+    // The action falls due on the first failure, the link is back before the countdown ends
+    sys.push_sequence("gone", vec![false, true]);
+
+    let mut cfg = cfg(vec!["gone"], false, 1);
+    cfg.countdown = Some(Duration::from_secs(3600));
+    let metrics = Arc::new(Mutex::new(Metrics::new()));
+
+    test_emergency_loop(&cfg, &metrics, &sys);
+    assert_eq!(sys.take_shutdowns(), 0);
+    let broadcasts = sys.take_broadcasts();
+    assert_eq!(broadcasts.len(), 2);
+    assert!(broadcasts[0].contains("poweroff"));
+    assert!(broadcasts[1].contains("cancelled"));
+}
+
+#[cfg(unix)]
+#[test]
+fn veto_hook_keeps_the_machine_up() {
+    use pingdown::hook::Hook;
+
+    let sys = StubSystem::new();
+    sys.push_sequence("gone", vec![false, true]);
+
+    let mut cfg = cfg(vec!["gone"], false, 1);
+    cfg.pre_action_hooks = vec![Hook::new("test \"$PINGDOWN_FAILING_TARGETS\" != gone", true)];
+    let metrics = Arc::new(Mutex::new(Metrics::new()));
+
+    test_emergency_loop(&cfg, &metrics, &sys);
+    assert_eq!(sys.take_shutdowns(), 0);
+}
+
+#[cfg(unix)]
+#[test]
+fn transition_hooks_fire_on_retries_and_recovery() {
+    use pingdown::hook::{HookEvent, TransitionHook};
+
+    let sys = StubSystem::new();
+    sys.push_sequence("flaky", vec![false, true]);
+
+    let log = tempfile::NamedTempFile::new().unwrap();
+    let record = |event| TransitionHook::new(event, format!("echo \"$PINGDOWN_EVENT\" >> {}", log.path().display()));
+    let mut cfg = cfg(vec!["flaky"], false, 2);
+    cfg.transition_hooks = vec![record(HookEvent::Retry), record(HookEvent::Recovery)];
+    let metrics = Arc::new(Mutex::new(Metrics::new()));
+
+    test_emergency_loop(&cfg, &metrics, &sys);
+    assert_eq!(std::fs::read_to_string(log.path()).unwrap(), "retry\nrecovery\n");
+}
+
+fn remediation_step(command: &str) -> pingdown::remediation::RemediationStep {
+    let mut step = pingdown::remediation::RemediationStep::new(command);
+    step.settle = Duration::ZERO;
+    step
+}
+
+#[test]
+fn successful_remediation_stands_down() {
+    let sys = StubSystem::new();
+    // Retries run out on the first failure; the re-probe after the first step succeeds
+    sys.push_sequence("gone", vec![false, true]);
+
+    let mut cfg = cfg(vec!["gone"], false, 1);
+    cfg.remediation = vec![remediation_step("restart-interface"), remediation_step("restart-networkmanager")];
+    let metrics = Arc::new(Mutex::new(Metrics::new()));
+
+    test_emergency_loop(&cfg, &metrics, &sys);
+    assert_eq!(sys.take_shutdowns(), 0);
+    // The second step was never needed
+    assert!(!sys.responses.lock().unwrap().contains_key("restart-networkmanager"));
+}
+
+#[test]
+fn failed_remediation_escalates_to_the_action() {
+    let sys = StubSystem::new();
+    sys.push_sequence("gone", vec![false, false, false, true]);
+
+    let mut cfg = cfg(vec!["gone"], false, 1);
+    cfg.remediation = vec![remediation_step("restart-interface"), remediation_step("renew-dhcp")];
+    let metrics = Arc::new(Mutex::new(Metrics::new()));
+
+    test_emergency_loop(&cfg, &metrics, &sys);
+    assert_eq!(sys.take_shutdowns(), 1);
+}
+
+#[test]
+fn escalation_ladder_runs_each_stage_in_turn() {
+    use pingdown::action::{Action, ActionKind};
+    use pingdown::escalation::EscalationStage;
+
+    let stage = |name: &str, kind| EscalationStage {
+        name: name.to_string(),
+        emergency_interval: Duration::from_secs(1),
+        emergency_retries: NonZeroU32::new(1).unwrap(),
+        actions: vec![Action::new(kind)],
+    };
+    let sys = StubSystem::new();
+    // Stage 1 acts on the first failure; after a fresh countdown stage 2 acts on the third
+    sys.push_sequence("gone", vec![false, false, false, true]);
+
+    let mut cfg = cfg(vec!["gone"], false, 5);
+    cfg.escalation = vec![stage("notify", ActionKind::LogOnly), stage("power off", ActionKind::Poweroff)];
+    let metrics = Arc::new(Mutex::new(Metrics::new()));
+
+    test_emergency_loop(&cfg, &metrics, &sys);
+    assert_eq!(sys.take_shutdowns(), 1);
+    assert_eq!(metrics.lock().unwrap().total_stage_escalations, Some(2));
+}
+
+#[test]
+fn recovery_in_the_first_stage_never_escalates() {
+    use pingdown::action::{Action, ActionKind};
+    use pingdown::escalation::EscalationStage;
+
+    let sys = StubSystem::new();
+    sys.push_sequence("flaky", vec![false, true]);
+
+    let mut cfg = cfg(vec!["flaky"], false, 1);
+    cfg.escalation = vec![EscalationStage {
+        name: "reboot".to_string(),
+        emergency_interval: Duration::from_secs(1),
+        emergency_retries: NonZeroU32::new(3).unwrap(),
+        actions: vec![Action::new(ActionKind::Poweroff)],
+    }];
+    let metrics = Arc::new(Mutex::new(Metrics::new()));
+
+    test_emergency_loop(&cfg, &metrics, &sys);
+    // The stage's three tries replace --tries 1
+    assert_eq!(sys.take_shutdowns(), 0);
+    assert_eq!(metrics.lock().unwrap().total_stage_escalations, Some(0));
+}
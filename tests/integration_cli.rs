@@ -1,5 +1,4 @@
 use assert_cmd::cargo::CommandCargoExt;
-use std::io::Write;
 use std::process::{Command, Stdio};
 use std::{thread, time::Duration};
 use tempfile::TempDir;
@@ -58,7 +57,14 @@ fn metrics_persist_to_temp_dir() {
     let orig = std::env::current_dir().unwrap();
     std::env::set_current_dir(tmp.path()).unwrap();
 
-    let metrics = Metrics { total_succeeds: 2, total_failures: 1, total_normal_loop_times: 3, total_emergency_loop_times: 0 };
+    let metrics = Metrics {
+        total_succeeds: 2,
+        total_failures: 1,
+        total_normal_loop_times: 3,
+        total_emergency_loop_times: 0,
+        total_stage_escalations: None,
+        action_log: Vec::new(),
+    };
     metrics.write();
 
     // Restore cwd to avoid affecting other tests
@@ -67,3 +73,50 @@ fn metrics_persist_to_temp_dir() {
     let contents = std::fs::read_to_string(tmp.path().join("pingdown_runtime_info.txt")).expect("runtime file present");
     assert!(contents.contains("total_succeeds: 2"));
 }
+
+#[test]
+fn doctor_reports_invalid_config_and_exits_with_failure() {
+    let tmp = TempDir::new().unwrap();
+    let mut cmd: Command = Command::cargo_bin("pingdown").unwrap();
+    let output = cmd.arg("doctor").current_dir(tmp.path()).output().expect("run pingdown doctor");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stdout.contains("[DOCTOR]"));
+    assert!(stdout.contains("configuration"));
+    assert!(stdout.contains("FAIL"));
+}
+
+#[test]
+fn doctor_checks_targets_given_before_the_subcommand() {
+    let tmp = TempDir::new().unwrap();
+    let mut cmd: Command = Command::cargo_bin("pingdown").unwrap();
+    let output = cmd.args(["127.0.0.1", "doctor"]).current_dir(tmp.path()).output().expect("run pingdown doctor");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(stdout.contains("[DOCTOR]"), "{}", stdout);
+    assert!(stdout.contains("valid, 1 target(s)"), "{}", stdout);
+}
+
+#[test]
+fn dry_run_exit_logs_actions_and_exits_with_code_3() {
+    let tmp = TempDir::new().unwrap();
+    let mut cmd: Command = Command::cargo_bin("pingdown").unwrap();
+    cmd.args(["--dry-run=exit", "-n", "1", "-e", "1", "-t", "1", "--status-only", "host.invalid"])
+        .current_dir(tmp.path())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = cmd.spawn().expect("spawn pingdown");
+
+    // One failed check escalates, the next one exhausts the single retry
+    let deadline = std::time::Instant::now() + Duration::from_secs(30);
+    while child.try_wait().unwrap().is_none() && std::time::Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(100));
+    }
+    let _ = child.kill();
+    let output = child.wait_with_output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert_eq!(output.status.code(), Some(3), "stdout: {}", stdout);
+    assert!(stdout.contains("[DRY-RUN] would run"));
+}
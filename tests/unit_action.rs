@@ -0,0 +1,124 @@
+mod common;
+use common::StubSystem;
+
+use std::time::Duration;
+
+use pingdown::action::{run_actions, Action, ActionKind, ActionResult, ActionSpec, DryRun};
+
+#[test]
+fn action_specs_are_validated_per_field() {
+    let spec = ActionSpec { kind: "reboot".to_string(), timeout: Some(15), ..ActionSpec::default() };
+    let action = Action::parse(&spec).unwrap();
+    assert_eq!(action.kind, ActionKind::Reboot);
+    assert_eq!(action.timeout, Duration::from_secs(15));
+
+    let spec = ActionSpec { kind: "run-command".to_string(), ..ActionSpec::default() };
+    assert_eq!(Action::parse(&spec).unwrap_err().0, "command");
+
+    let spec = ActionSpec { kind: "explode".to_string(), ..ActionSpec::default() };
+    assert_eq!(Action::parse(&spec).unwrap_err().0, "type");
+
+    let spec = ActionSpec { kind: "poweroff".to_string(), timeout: Some(0), ..ActionSpec::default() };
+    assert_eq!(Action::parse(&spec).unwrap_err().0, "timeout");
+}
+
+#[test]
+fn cli_form_splits_run_command() {
+    let spec = ActionSpec::from_cli("run-command:systemctl stop nginx");
+    let action = Action::parse(&spec).unwrap();
+    assert_eq!(action.kind, ActionKind::RunCommand("systemctl stop nginx".to_string()));
+    assert_eq!(action.to_string(), "run-command 'systemctl stop nginx'");
+}
+
+#[test]
+fn pipeline_runs_every_action_in_order_and_records_results() {
+    let sys = StubSystem::new();
+    let actions = vec![
+        Action::new(ActionKind::LogOnly),
+        Action::new(ActionKind::Suspend),
+        Action::new(ActionKind::Poweroff),
+    ];
+
+    let outcomes = run_actions(&actions, &sys, DryRun::Off, |_| {});
+    let results: Vec<&ActionResult> = outcomes.iter().map(|outcome| &outcome.result).collect();
+    assert_eq!(results[0], &ActionResult::Ok);
+    // The stub only knows how to power off; a failure does not stop the pipeline
+    assert!(matches!(results[1], ActionResult::Failed(_)));
+    assert_eq!(results[2], &ActionResult::Ok);
+    assert_eq!(sys.take_shutdowns(), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn commands_are_killed_after_their_timeout() {
+    use pingdown::system::{DefaultSystem, System};
+
+    let sys = DefaultSystem::new();
+    let result = sys.run_action(&ActionKind::RunCommand("sleep 5".to_string()), Duration::from_millis(200));
+    assert_eq!(result, ActionResult::TimedOut);
+
+    let result = sys.run_action(&ActionKind::RunCommand("exit 3".to_string()), Duration::from_secs(5));
+    assert!(matches!(result, ActionResult::Failed(_)));
+}
+
+#[cfg(unix)]
+#[test]
+fn attempts_keep_the_command_status_stderr_and_duration() {
+    use pingdown::system::{DefaultSystem, System};
+
+    let sys = DefaultSystem::new();
+    let command = "echo 'not permitted' >&2; exit 4";
+    let (result, attempts) = sys.run_action_recorded(&ActionKind::RunCommand(command.to_string()), Duration::from_secs(5));
+    assert!(matches!(result, ActionResult::Failed(_)));
+    assert_eq!(attempts.len(), 1);
+    let attempt = &attempts[0];
+    assert_eq!(attempt.command, command);
+    assert!(attempt.status.as_deref().unwrap().contains('4'), "{:?}", attempt.status);
+    assert_eq!(attempt.stderr, "not permitted");
+    assert!(attempt.elapsed < Duration::from_secs(5));
+    assert!(attempt.to_string().contains("(not permitted)"), "{}", attempt);
+
+    let (result, attempts) = sys.run_action_recorded(&ActionKind::RunCommand("sleep 5".to_string()), Duration::from_millis(200));
+    assert_eq!(result, ActionResult::TimedOut);
+    assert_eq!(attempts[0].status, None);
+}
+
+#[cfg(unix)]
+#[test]
+fn chatty_commands_finish_and_timeouts_kill_what_they_started() {
+    use pingdown::system::{DefaultSystem, System};
+
+    let sys = DefaultSystem::new();
+    // More stderr than a pipe holds must not stall the command
+    let command = "head -c 200000 /dev/zero | tr '\\0' x >&2; echo done >&2";
+    let (result, attempts) = sys.run_action_recorded(&ActionKind::RunCommand(command.to_string()), Duration::from_secs(5));
+    assert_eq!(result, ActionResult::Ok);
+    assert!(attempts[0].stderr.starts_with("xxx"));
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let pid_file = dir.path().join("pid");
+    let command = format!("sleep 30 & echo $! > {}; wait", pid_file.display());
+    let result = sys.run_action(&ActionKind::RunCommand(command), Duration::from_millis(300));
+    assert_eq!(result, ActionResult::TimedOut);
+    let pid = std::fs::read_to_string(&pid_file).unwrap();
+    let stat = format!("/proc/{}/stat", pid.trim());
+    // Gone, or a zombie waiting for init to reap it
+    let alive = (0..20).all(|_| {
+        std::thread::sleep(Duration::from_millis(50));
+        std::fs::read_to_string(&stat).is_ok_and(|stat| !stat.contains(") Z "))
+    });
+    assert!(!alive, "background sleep {} survived the timeout", pid.trim());
+}
+
+#[test]
+fn dry_run_only_logs_the_commands() {
+    let sys = StubSystem::new();
+    let actions = vec![
+        Action::new(ActionKind::Poweroff),
+        Action::new(ActionKind::RunCommand("systemctl stop app".to_string())),
+    ];
+
+    let outcomes = run_actions(&actions, &sys, DryRun::Continue, |_| {});
+    assert!(outcomes.iter().all(|outcome| outcome.result == ActionResult::DryRun));
+    assert_eq!(sys.take_shutdowns(), 0);
+}
@@ -2,6 +2,7 @@ use clap::Parser;
 use pingdown::cli::Cli;
 use pingdown::config::{build_monitor_config, ConfigError, ENV_CONFIG_PATH};
 use std::io::Write;
+use std::time::Duration;
 use tempfile::NamedTempFile;
 
 #[test]
@@ -39,9 +40,8 @@ fn cli_only_arguments_build_monitor_config() {
 #[test]
 fn cli_values_override_file_settings() {
     let mut file = NamedTempFile::new().expect("temp file");
-    write!(
-        file,
-        r#"{
+    file.write_all(
+        br#"{
             "address": ["file.example"],
             "strict": false,
             "secs-for-normal-loop": 60,
@@ -84,9 +84,8 @@ fn cli_values_override_file_settings() {
 #[test]
 fn environment_variable_selects_configuration_file() {
     let mut file = NamedTempFile::new().expect("temp file");
-    write!(
-        file,
-        r#"{
+    file.write_all(
+        br#"{
             "address": ["env.example"],
             "strict": true,
             "secs-for-normal-loop": 42,
@@ -156,9 +155,8 @@ fn zero_values_are_rejected_with_field_context() {
 #[test]
 fn file_validation_errors_preserve_field_paths() {
     let mut file = NamedTempFile::new().expect("temp file");
-    write!(
-        file,
-        r#"{
+    file.write_all(
+        br#"{
             "address": ["file.target"],
             "secs-for-normal-loop": 10,
             "secs-for-emergency-loop": 5,
@@ -179,3 +177,698 @@ fn file_validation_errors_preserve_field_paths() {
         other => panic!("unexpected error: {}", other),
     }
 }
+
+#[test]
+fn hysteresis_thresholds_default_to_single_cycle() {
+    let cli = Cli::parse_from(vec!["pingdown", "1.1.1.1"]);
+    let cfg = build_monitor_config(&cli).expect("defaults should succeed");
+    assert_eq!(cfg.fail_threshold_cycles(), 1);
+    assert_eq!(cfg.recover_threshold_cycles(), 1);
+}
+
+#[test]
+fn hysteresis_thresholds_merge_file_and_cli() {
+    let mut file = NamedTempFile::new().expect("temp file");
+    file.write_all(
+        br#"{
+            "address": ["file.target"],
+            "fail-threshold": 3,
+            "recover-threshold": 2
+        }"#
+    )
+    .unwrap();
+    file.flush().unwrap();
+
+    let path = file.path().to_str().unwrap();
+    let cli = Cli::parse_from(vec!["pingdown", "--config", path]);
+    let cfg = build_monitor_config(&cli).expect("file thresholds should succeed");
+    assert_eq!(cfg.fail_threshold_cycles(), 3);
+    assert_eq!(cfg.recover_threshold_cycles(), 2);
+
+    let cli = Cli::parse_from(vec!["pingdown", "--config", path, "--recover-threshold", "4"]);
+    let cfg = build_monitor_config(&cli).expect("cli threshold should succeed");
+    assert_eq!(cfg.fail_threshold_cycles(), 3); // File value retained
+    assert_eq!(cfg.recover_threshold_cycles(), 4); // CLI overrides file
+
+    let cli = Cli::parse_from(vec!["pingdown", "1.1.1.1", "--fail-threshold", "0"]);
+    let err = build_monitor_config(&cli).expect_err("zero fail threshold should fail");
+    match err {
+        ConfigError::Validation { field_path, .. } => {
+            assert_eq!(field_path, "cli --fail-threshold");
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn flap_detection_settings_are_read_from_file() {
+    use pingdown::flap::FlapAction;
+
+    let mut file = NamedTempFile::new().expect("temp file");
+    file.write_all(
+        br#"{
+            "address": ["file.target"],
+            "flap-window": 600,
+            "flap-threshold": 4,
+            "flap-action": "defer"
+        }"#
+    )
+    .unwrap();
+    file.flush().unwrap();
+
+    let cli = Cli::parse_from(vec!["pingdown", "--config", file.path().to_str().unwrap()]);
+    let cfg = build_monitor_config(&cli).expect("flap configuration should succeed");
+    assert_eq!(cfg.flap_window.as_secs(), 600);
+    assert_eq!(cfg.flap_threshold, 4);
+    assert_eq!(cfg.flap_action, FlapAction::Defer);
+
+    let defaults = build_monitor_config(&Cli::parse_from(vec!["pingdown", "1.1.1.1"])).unwrap();
+    assert!(defaults.flap_window.is_zero());
+    assert_eq!(defaults.flap_action, FlapAction::None);
+
+    let path = file.path().to_str().unwrap();
+    let cli = Cli::parse_from(vec!["pingdown", "--config", path, "--flap-window", "120", "--flap-action", "suppress"]);
+    let cfg = build_monitor_config(&cli).expect("cli flap flags should succeed");
+    assert_eq!(cfg.flap_window.as_secs(), 120);
+    assert_eq!(cfg.flap_threshold, 4); // File value retained
+    assert_eq!(cfg.flap_action, FlapAction::Suppress);
+
+    let cli = Cli::parse_from(vec!["pingdown", "1.1.1.1", "--flap-window", "60", "--flap-threshold", "1"]);
+    match build_monitor_config(&cli).expect_err("a single transition is not flapping") {
+        ConfigError::Validation { field_path, .. } => assert_eq!(field_path, "cli --flap-threshold"),
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn availability_policy_is_validated() {
+    use pingdown::config::{AvailabilityBasis, DecisionPolicy};
+
+    let mut file = NamedTempFile::new().expect("temp file");
+    file.write_all(
+        br#"{
+            "address": ["file.target"],
+            "policy": "availability",
+            "availability-window": 900,
+            "availability-threshold": 80,
+            "availability-basis": "probes"
+        }"#
+    )
+    .unwrap();
+    file.flush().unwrap();
+
+    let cli = Cli::parse_from(vec!["pingdown", "--config", file.path().to_str().unwrap()]);
+    let cfg = build_monitor_config(&cli).expect("availability configuration should succeed");
+    assert_eq!(cfg.policy, DecisionPolicy::Availability);
+    assert_eq!(cfg.availability_window.as_secs(), 900);
+    assert_eq!(cfg.availability_threshold, 80);
+    assert_eq!(cfg.availability_basis, AvailabilityBasis::Probes);
+
+    let mut file = NamedTempFile::new().expect("temp file");
+    file.write_all(br#"{ "address": ["file.target"], "availability-threshold": 101 }"#).unwrap();
+    file.flush().unwrap();
+
+    let cli = Cli::parse_from(vec!["pingdown", "--config", file.path().to_str().unwrap()]);
+    match build_monitor_config(&cli).expect_err("threshold above 100 should fail") {
+        ConfigError::Validation { field_path, .. } => {
+            assert_eq!(field_path, format!("{}:availability-threshold", file.path().display()));
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+
+    let cli = Cli::parse_from(vec![
+        "pingdown",
+        "1.1.1.1",
+        "--policy",
+        "availability",
+        "--availability-window",
+        "300",
+        "--availability-basis",
+        "probes",
+    ]);
+    let cfg = build_monitor_config(&cli).expect("cli availability flags should succeed");
+    assert_eq!(cfg.policy, DecisionPolicy::Availability);
+    assert_eq!(cfg.availability_window.as_secs(), 300);
+    assert_eq!(cfg.availability_threshold, 90);
+    assert_eq!(cfg.availability_basis, AvailabilityBasis::Probes);
+
+    let cli = Cli::parse_from(vec!["pingdown", "1.1.1.1", "--policy", "availability", "--availability-threshold", "0"]);
+    match build_monitor_config(&cli).expect_err("zero threshold should fail") {
+        ConfigError::Validation { field_path, .. } => assert_eq!(field_path, "cli --availability-threshold"),
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn max_outage_is_optional_and_rejects_zero() {
+    let cli = Cli::parse_from(vec!["pingdown", "1.1.1.1"]);
+    let cfg = build_monitor_config(&cli).unwrap();
+    assert_eq!(cfg.max_outage, None);
+
+    let cli = Cli::parse_from(vec!["pingdown", "1.1.1.1", "--max-outage", "300"]);
+    let cfg = build_monitor_config(&cli).unwrap();
+    assert_eq!(cfg.max_outage.map(|d| d.as_secs()), Some(300));
+
+    let cli = Cli::parse_from(vec!["pingdown", "1.1.1.1", "--max-outage", "0"]);
+    match build_monitor_config(&cli).expect_err("zero max outage should fail") {
+        ConfigError::Validation { field_path, .. } => assert_eq!(field_path, "cli --max-outage"),
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn countdown_zero_means_no_countdown() {
+    let cli = Cli::parse_from(vec!["pingdown", "1.1.1.1", "--countdown", "120"]);
+    let cfg = build_monitor_config(&cli).unwrap();
+    assert_eq!(cfg.countdown, Some(Duration::from_secs(120)));
+
+    let cli = Cli::parse_from(vec!["pingdown", "1.1.1.1", "--countdown", "18446744073709551615"]);
+    match build_monitor_config(&cli).expect_err("an endless countdown should fail") {
+        ConfigError::Validation { field_path, .. } => assert_eq!(field_path, "cli --countdown"),
+        other => panic!("unexpected error: {}", other),
+    }
+
+    let cli = Cli::parse_from(vec!["pingdown", "1.1.1.1", "--countdown", "0"]);
+    let cfg = build_monitor_config(&cli).unwrap();
+    assert_eq!(cfg.countdown, None);
+}
+
+#[test]
+fn maintenance_windows_are_parsed_with_field_paths() {
+    let mut file = NamedTempFile::new().expect("temp file");
+    file.write_all(
+        br#"{
+            "address": ["file.target"],
+            "maintenance-windows": [
+                { "days": ["mon", "tue"], "start": "01:00", "end": "05:00" },
+                { "start": "23:30", "end": "00:30" }
+            ]
+        }"#
+    )
+    .unwrap();
+    file.flush().unwrap();
+
+    let cli = Cli::parse_from(vec!["pingdown", "--config", file.path().to_str().unwrap()]);
+    let cfg = build_monitor_config(&cli).expect("maintenance windows should parse");
+    assert_eq!(cfg.maintenance_windows.len(), 2);
+    assert_eq!(cfg.maintenance_windows[0].to_string(), "mon,tue 01:00-05:00");
+
+    let mut file = NamedTempFile::new().expect("temp file");
+    file.write_all(
+        br#"{
+            "address": ["file.target"],
+            "maintenance-windows": [{ "start": "01:00", "end": "5am" }]
+        }"#
+    )
+    .unwrap();
+    file.flush().unwrap();
+
+    let cli = Cli::parse_from(vec!["pingdown", "--config", file.path().to_str().unwrap()]);
+    match build_monitor_config(&cli).expect_err("bad end time should fail") {
+        ConfigError::Validation { field_path, .. } => {
+            assert_eq!(field_path, format!("{}:maintenance-windows[0].end", file.path().display()));
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn profiles_override_only_the_values_they_set() {
+    let mut file = NamedTempFile::new().expect("temp file");
+    file.write_all(
+        br#"{
+            "address": ["file.target"],
+            "secs-for-normal-loop": 30,
+            "secs-for-emergency-loop": 10,
+            "times-for-emergency-loop": 3,
+            "profiles": [
+                {
+                    "name": "night",
+                    "start": "22:00",
+                    "end": "07:00",
+                    "secs-for-normal-loop": 300,
+                    "times-for-emergency-loop": 10
+                },
+                { "name": "business", "days": ["mon", "fri"], "start": "09:00", "end": "17:00", "strict": true }
+            ]
+        }"#
+    )
+    .unwrap();
+    file.flush().unwrap();
+
+    let cli = Cli::parse_from(vec!["pingdown", "--config", file.path().to_str().unwrap()]);
+    let cfg = build_monitor_config(&cli).expect("profiles should parse");
+    assert_eq!(cfg.profiles.len(), 2);
+
+    let night = cfg.with_profile(&cfg.profiles[0]);
+    assert_eq!(night.normal_interval_secs(), 300);
+    assert_eq!(night.emergency_interval_secs(), 10); // Base value retained
+    assert_eq!(night.emergency_retry_attempts(), 10);
+    assert!(!night.strict);
+
+    let business = cfg.with_profile(&cfg.profiles[1]);
+    assert_eq!(business.normal_interval_secs(), 30);
+    assert!(business.strict);
+}
+
+#[test]
+fn invalid_profile_reports_field_path() {
+    let mut file = NamedTempFile::new().expect("temp file");
+    file.write_all(
+        br#"{
+            "address": ["file.target"],
+            "profiles": [{ "name": "night", "start": "22:00", "end": "07:00", "secs-for-emergency-loop": 0 }]
+        }"#
+    )
+    .unwrap();
+    file.flush().unwrap();
+
+    let cli = Cli::parse_from(vec!["pingdown", "--config", file.path().to_str().unwrap()]);
+    match build_monitor_config(&cli).expect_err("zero profile interval should fail") {
+        ConfigError::Validation { field_path, .. } => {
+            assert_eq!(field_path, format!("{}:profiles[0].secs-for-emergency-loop", file.path().display()));
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn startup_grace_reads_clock_and_zero_disables() {
+    use pingdown::config::GraceClock;
+
+    let mut file = NamedTempFile::new().expect("temp file");
+    file.write_all(br#"{ "address": ["file.target"], "startup-grace": 180, "grace-clock": "uptime" }"#).unwrap();
+    file.flush().unwrap();
+
+    let path = file.path().to_str().unwrap();
+    let cfg = build_monitor_config(&Cli::parse_from(vec!["pingdown", "--config", path])).unwrap();
+    assert_eq!(cfg.startup_grace.map(|d| d.as_secs()), Some(180));
+    assert_eq!(cfg.grace_clock, GraceClock::Uptime);
+
+    let cli = Cli::parse_from(vec!["pingdown", "--config", path, "--startup-grace", "0"]);
+    let cfg = build_monitor_config(&cli).unwrap();
+    assert_eq!(cfg.startup_grace, None);
+}
+
+#[test]
+fn jitter_accepts_percent_or_fixed_spread_but_not_both() {
+    use pingdown::jitter::JitterSpread;
+    use std::time::Duration;
+
+    let mut file = NamedTempFile::new().expect("temp file");
+    file.write_all(
+        br#"{ "address": ["file.target"], "jitter-secs": 5, "jitter-seed": 7, "initial-offset": 30 }"#,
+    )
+    .unwrap();
+    file.flush().unwrap();
+
+    let path = file.path().to_str().unwrap();
+    let cfg = build_monitor_config(&Cli::parse_from(vec!["pingdown", "--config", path])).unwrap();
+    assert_eq!(cfg.jitter, JitterSpread::Fixed(Duration::from_secs(5)));
+    assert_eq!(cfg.jitter_seed, Some(7));
+    assert_eq!(cfg.initial_offset.as_secs(), 30);
+
+    let cli = Cli::parse_from(vec!["pingdown", "--config", path, "--jitter", "15"]);
+    let cfg = build_monitor_config(&cli).unwrap();
+    assert_eq!(cfg.jitter, JitterSpread::Percent(15)); // CLI overrides file
+
+    let cli = Cli::parse_from(vec!["pingdown", "--config", path, "--jitter-secs", "30", "--jitter-seed", "9"]);
+    let cfg = build_monitor_config(&cli).unwrap();
+    assert_eq!(cfg.jitter, JitterSpread::Fixed(Duration::from_secs(30)));
+    assert_eq!(cfg.jitter_seed, Some(9));
+
+    let cli = Cli::parse_from(vec!["pingdown", "8.8.8.8", "--jitter", "10", "--jitter-secs", "30"]);
+    match build_monitor_config(&cli).expect_err("both CLI spreads should fail") {
+        ConfigError::Validation { field_path, .. } => assert_eq!(field_path, "cli --jitter-secs"),
+        other => panic!("unexpected error: {}", other),
+    }
+
+    let cli = Cli::parse_from(vec!["pingdown", "8.8.8.8", "--jitter-secs", "18446744073709551615"]);
+    match build_monitor_config(&cli).expect_err("an endless spread should fail") {
+        ConfigError::Validation { field_path, .. } => assert_eq!(field_path, "cli --jitter-secs"),
+        other => panic!("unexpected error: {}", other),
+    }
+
+    let mut file = NamedTempFile::new().expect("temp file");
+    file.write_all(br#"{ "address": ["file.target"], "jitter-percent": 10, "jitter-secs": 5 }"#).unwrap();
+    file.flush().unwrap();
+
+    let cli = Cli::parse_from(vec!["pingdown", "--config", file.path().to_str().unwrap()]);
+    match build_monitor_config(&cli).expect_err("both spreads should fail") {
+        ConfigError::Validation { field_path, .. } => {
+            assert_eq!(field_path, format!("{}:jitter-secs", file.path().display()));
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn dependencies_accept_gateway_and_reject_cycles() {
+    let cli = Cli::parse_from(vec!["pingdown", "8.8.8.8", "@gateway", "--depends", "8.8.8.8=@gateway"]);
+    let cfg = build_monitor_config(&cli).expect("gateway dependency should build");
+    assert_eq!(cfg.dependencies.get("8.8.8.8").map(String::as_str), Some("@gateway"));
+
+    let cli = Cli::parse_from(vec!["pingdown", "8.8.8.8", "--depends", "1.1.1.1=@gateway"]);
+    match build_monitor_config(&cli).expect_err("unknown child should fail") {
+        ConfigError::Validation { field_path, .. } => assert_eq!(field_path, "cli --depends.1.1.1.1"),
+        other => panic!("unexpected error: {}", other),
+    }
+
+    let mut file = NamedTempFile::new().expect("temp file");
+    file.write_all(
+        br#"{
+            "address": ["a.example.com", "b.example.com"],
+            "dependencies": { "a.example.com": "b.example.com", "b.example.com": "a.example.com" }
+        }"#,
+    )
+    .unwrap();
+    file.flush().unwrap();
+
+    let cli = Cli::parse_from(vec!["pingdown", "--config", file.path().to_str().unwrap()]);
+    match build_monitor_config(&cli).expect_err("cycle should fail") {
+        ConfigError::Validation { field_path, message } => {
+            assert_eq!(field_path, format!("{}:dependencies.a.example.com", file.path().display()));
+            assert!(message.contains("cycle"));
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn actions_default_to_poweroff_and_merge_from_cli() {
+    use pingdown::action::ActionKind;
+
+    let cli = Cli::parse_from(vec!["pingdown", "1.1.1.1"]);
+    let cfg = build_monitor_config(&cli).unwrap();
+    let kinds: Vec<ActionKind> = cfg.actions.iter().map(|action| action.kind.clone()).collect();
+    assert_eq!(kinds, vec![ActionKind::Poweroff]);
+
+    let mut file = NamedTempFile::new().expect("temp file");
+    file.write_all(
+        br#"{
+            "address": ["file.target"],
+            "actions": [
+                { "type": "run-command", "command": "systemctl stop app", "timeout": 30 },
+                { "type": "reboot" }
+            ]
+        }"#,
+    )
+    .unwrap();
+    file.flush().unwrap();
+
+    let path = file.path().to_str().unwrap();
+    let cfg = build_monitor_config(&Cli::parse_from(vec!["pingdown", "--config", path])).unwrap();
+    assert_eq!(cfg.actions.len(), 2);
+    assert_eq!(cfg.actions[0].timeout.as_secs(), 30);
+    assert_eq!(cfg.actions[1].kind, ActionKind::Reboot);
+
+    // CLI replaces the file's pipeline
+    let cli = Cli::parse_from(vec!["pingdown", "--config", path, "--action", "log-only"]);
+    let cfg = build_monitor_config(&cli).unwrap();
+    assert_eq!(cfg.actions.len(), 1);
+    assert_eq!(cfg.actions[0].kind, ActionKind::LogOnly);
+
+    let cli = Cli::parse_from(vec!["pingdown", "1.1.1.1", "--action", "halt"]);
+    match build_monitor_config(&cli).expect_err("unknown action should fail") {
+        ConfigError::Validation { field_path, .. } => assert_eq!(field_path, "cli --action[0].type"),
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn dry_run_flag_defaults_to_continue() {
+    use pingdown::action::DryRun;
+
+    let cfg = build_monitor_config(&Cli::parse_from(vec!["pingdown", "1.1.1.1"])).unwrap();
+    assert_eq!(cfg.dry_run, DryRun::Off);
+
+    let cfg = build_monitor_config(&Cli::parse_from(vec!["pingdown", "--dry-run", "1.1.1.1"])).unwrap();
+    assert_eq!(cfg.dry_run, DryRun::Continue);
+
+    let cfg = build_monitor_config(&Cli::parse_from(vec!["pingdown", "--dry-run=exit", "1.1.1.1"])).unwrap();
+    assert_eq!(cfg.dry_run, DryRun::Exit);
+
+    let mut file = NamedTempFile::new().expect("temp file");
+    file.write_all(br#"{ "address": ["file.target"], "dry-run": "exit" }"#).unwrap();
+    file.flush().unwrap();
+    let cli = Cli::parse_from(vec!["pingdown", "--config", file.path().to_str().unwrap()]);
+    assert_eq!(build_monitor_config(&cli).unwrap().dry_run, DryRun::Exit);
+}
+
+#[test]
+fn pre_action_hooks_merge_from_cli_and_report_field_paths() {
+    let cli = Cli::parse_from(vec!["pingdown", "1.1.1.1", "--pre-action-hook", "sync", "--veto-hook", "/etc/pingdown/check"]);
+    let cfg = build_monitor_config(&cli).unwrap();
+    let hooks: Vec<(&str, bool)> = cfg.pre_action_hooks.iter().map(|hook| (hook.command.as_str(), hook.veto)).collect();
+    assert_eq!(hooks, vec![("sync", false), ("/etc/pingdown/check", true)]);
+
+    let mut file = NamedTempFile::new().expect("temp file");
+    file.write_all(
+        br#"{
+            "address": ["file.target"],
+            "pre-action-hooks": [
+                { "command": "pg_ctl stop", "timeout": 120, "veto": true },
+                { "command": "" }
+            ]
+        }"#,
+    )
+    .unwrap();
+    file.flush().unwrap();
+    let cli = Cli::parse_from(vec!["pingdown", "--config", file.path().to_str().unwrap()]);
+    match build_monitor_config(&cli).expect_err("empty hook command should fail") {
+        ConfigError::Validation { field_path, .. } => {
+            assert_eq!(field_path, format!("{}:pre-action-hooks[1].command", file.path().display()));
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn transition_hooks_merge_from_cli_and_report_field_paths() {
+    use pingdown::hook::HookEvent;
+
+    let cli = Cli::parse_from(vec!["pingdown", "1.1.1.1", "--hook", "emergency:vip up", "--hook", "recovery:vip down"]);
+    let cfg = build_monitor_config(&cli).unwrap();
+    let hooks: Vec<(HookEvent, &str)> = cfg.transition_hooks.iter().map(|hook| (hook.event, hook.command.as_str())).collect();
+    assert_eq!(hooks, vec![(HookEvent::Emergency, "vip up"), (HookEvent::Recovery, "vip down")]);
+
+    let cli = Cli::parse_from(vec!["pingdown", "1.1.1.1", "--hook", "reboot:true"]);
+    match build_monitor_config(&cli).expect_err("unknown event should fail") {
+        ConfigError::Validation { field_path, .. } => assert_eq!(field_path, "cli --hook[0].on"),
+        other => panic!("unexpected error: {}", other),
+    }
+
+    let mut file = NamedTempFile::new().expect("temp file");
+    file.write_all(
+        br#"{
+            "address": ["file.target"],
+            "hooks": [{ "on": "startup", "command": "logger pingdown up", "json": true, "timeout": 5 }]
+        }"#,
+    )
+    .unwrap();
+    file.flush().unwrap();
+    let cli = Cli::parse_from(vec!["pingdown", "--config", file.path().to_str().unwrap()]);
+    let cfg = build_monitor_config(&cli).unwrap();
+    assert_eq!(cfg.transition_hooks[0].event, HookEvent::Startup);
+    assert!(cfg.transition_hooks[0].json);
+    assert_eq!(cfg.transition_hooks[0].timeout.as_secs(), 5);
+}
+
+#[test]
+fn remediation_steps_merge_from_cli_and_report_field_paths() {
+    let cli = Cli::parse_from(vec!["pingdown", "1.1.1.1", "--remediate", "nmcli networking off && nmcli networking on"]);
+    let cfg = build_monitor_config(&cli).unwrap();
+    assert_eq!(cfg.remediation.len(), 1);
+    assert_eq!(cfg.remediation[0].name, "nmcli networking off && nmcli networking on");
+    assert_eq!(cfg.remediation[0].settle, Duration::from_secs(10));
+
+    let mut file = NamedTempFile::new().expect("temp file");
+    file.write_all(
+        br#"{
+            "address": ["file.target"],
+            "remediation": [
+                { "name": "renew DHCP", "command": "dhclient -r eth0 && dhclient eth0", "settle": 20 },
+                { "name": "restart NetworkManager", "command": "systemctl restart NetworkManager", "timeout": 0 }
+            ]
+        }"#,
+    )
+    .unwrap();
+    file.flush().unwrap();
+    let cli = Cli::parse_from(vec!["pingdown", "--config", file.path().to_str().unwrap()]);
+    match build_monitor_config(&cli).expect_err("zero timeout should fail") {
+        ConfigError::Validation { field_path, .. } => {
+            assert_eq!(field_path, format!("{}:remediation[1].timeout", file.path().display()));
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn escalation_stages_inherit_base_pacing_and_report_field_paths() {
+    use pingdown::action::ActionKind;
+
+    let mut file = NamedTempFile::new().expect("temp file");
+    file.write_all(
+        br#"{
+            "address": ["file.target"],
+            "secs-for-emergency-loop": 15,
+            "times-for-emergency-loop": 4,
+            "escalation": [
+                { "name": "notify", "interval": 5, "tries": 2, "actions": [{ "type": "log-only" }] },
+                { "actions": [{ "type": "reboot" }] }
+            ]
+        }"#,
+    )
+    .unwrap();
+    file.flush().unwrap();
+    let cli = Cli::parse_from(vec!["pingdown", "--config", file.path().to_str().unwrap()]);
+    let cfg = build_monitor_config(&cli).unwrap();
+    assert_eq!(cfg.escalation.len(), 2);
+    assert_eq!(cfg.escalation[0].emergency_interval, Duration::from_secs(5));
+    assert_eq!(cfg.escalation[0].emergency_retries.get(), 2);
+    assert_eq!(cfg.escalation[1].name, "stage 2");
+    assert_eq!(cfg.escalation[1].emergency_interval, Duration::from_secs(15));
+    assert_eq!(cfg.escalation[1].emergency_retries.get(), 4);
+    assert_eq!(cfg.escalation[1].actions[0].kind, ActionKind::Reboot);
+
+    let mut file = NamedTempFile::new().expect("temp file");
+    file.write_all(br#"{ "address": ["file.target"], "escalation": [{ "name": "notify", "actions": [{ "type": "shout" }] }] }"#)
+        .unwrap();
+    file.flush().unwrap();
+    let cli = Cli::parse_from(vec!["pingdown", "--config", file.path().to_str().unwrap()]);
+    match build_monitor_config(&cli).expect_err("unknown action should fail") {
+        ConfigError::Validation { field_path, .. } => {
+            assert_eq!(field_path, format!("{}:escalation[0].actions[0].type", file.path().display()));
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn action_limit_merges_from_cli_and_rejects_an_empty_window() {
+    let cli = Cli::parse_from(vec!["pingdown", "1.1.1.1", "--action-limit", "3"]);
+    let cfg = build_monitor_config(&cli).unwrap();
+    assert_eq!(cfg.action_limit.map(|limit| limit.get()), Some(3));
+    assert_eq!(cfg.action_limit_window, Duration::from_secs(24 * 60 * 60));
+
+    let cli = Cli::parse_from(vec!["pingdown", "1.1.1.1", "--action-limit", "0"]);
+    assert_eq!(build_monitor_config(&cli).unwrap().action_limit, None);
+
+    let mut file = NamedTempFile::new().expect("temp file");
+    file.write_all(br#"{ "address": ["file.target"], "action-limit": 2, "action-limit-window": 0 }"#).unwrap();
+    file.flush().unwrap();
+    let cli = Cli::parse_from(vec!["pingdown", "--config", file.path().to_str().unwrap()]);
+    match build_monitor_config(&cli).expect_err("zero window should fail") {
+        ConfigError::Validation { field_path, .. } => {
+            assert_eq!(field_path, format!("{}:action-limit-window", file.path().display()));
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn webhooks_merge_from_cli_and_report_field_paths() {
+    let cli = Cli::parse_from(vec!["pingdown", "1.1.1.1", "--webhook", "https://hooks.example/a", "--webhook-queue", "/tmp/q"]);
+    let cfg = build_monitor_config(&cli).unwrap();
+    assert_eq!(cfg.webhooks.len(), 1);
+    assert_eq!(cfg.webhooks[0].url, "https://hooks.example/a");
+    assert_eq!(cfg.webhooks[0].retries, 3);
+    assert_eq!(cfg.webhook_queue.as_deref(), Some(std::path::Path::new("/tmp/q")));
+
+    let cli = Cli::parse_from(vec!["pingdown", "1.1.1.1", "--webhook", "hooks.example"]);
+    match build_monitor_config(&cli).expect_err("missing scheme should fail") {
+        ConfigError::Validation { field_path, .. } => assert_eq!(field_path, "cli --webhook[0].url"),
+        other => panic!("unexpected error: {}", other),
+    }
+
+    let mut file = NamedTempFile::new().expect("temp file");
+    file.write_all(
+        br#"{
+            "address": ["file.target"],
+            "webhooks": [{ "url": "http://10.0.0.5/hook", "timeout": 3, "retries": 1 }, { "url": "http://x", "timeout": 0 }]
+        }"#,
+    )
+    .unwrap();
+    file.flush().unwrap();
+    let cli = Cli::parse_from(vec!["pingdown", "--config", file.path().to_str().unwrap()]);
+    match build_monitor_config(&cli).expect_err("zero timeout should fail") {
+        ConfigError::Validation { field_path, .. } => {
+            assert_eq!(field_path, format!("{}:webhooks[1].timeout", file.path().display()));
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn smtp_flags_patch_the_file_section_and_report_field_paths() {
+    let mut file = NamedTempFile::new().expect("temp file");
+    file.write_all(
+        br#"{
+            "address": ["file.target"],
+            "smtp": { "server": "relay.internal", "username": "alerts", "password": "secret",
+                      "from": "pingdown@example.com", "to": ["ops@example.com"] }
+        }"#,
+    )
+    .unwrap();
+    file.flush().unwrap();
+    let path = file.path().to_str().unwrap();
+    let cli = Cli::parse_from(vec!["pingdown", "--config", path, "--smtp", "mail.internal:2525", "--smtp-starttls"]);
+    let smtp = build_monitor_config(&cli).unwrap().smtp.unwrap();
+    assert_eq!((smtp.server.as_str(), smtp.port, smtp.starttls), ("mail.internal", 2525, true));
+    assert_eq!(smtp.credentials, Some(("alerts".to_string(), "secret".to_string())));
+    assert_eq!(smtp.to[0].to_string(), "ops@example.com");
+
+    let cli = Cli::parse_from(vec!["pingdown", "1.1.1.1", "--smtp", "relay", "--mail-to", "ops@example.com"]);
+    match build_monitor_config(&cli).expect_err("missing sender should fail") {
+        ConfigError::Validation { field_path, .. } => assert_eq!(field_path, "cli --smtp.from"),
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn mqtt_flags_patch_the_file_section_and_report_field_paths() {
+    let mut file = NamedTempFile::new().expect("temp file");
+    file.write_all(
+        br#"{ "address": ["file.target"], "mqtt": { "broker": "broker.lan", "topic-prefix": "home/pingdown", "username": "edge" } }"#,
+    )
+    .unwrap();
+    file.flush().unwrap();
+    let path = file.path().to_str().unwrap();
+    let cli = Cli::parse_from(vec!["pingdown", "--config", path, "--mqtt", "10.0.0.2:1884"]);
+    let mqtt = build_monitor_config(&cli).unwrap().mqtt.unwrap();
+    assert_eq!((mqtt.host.as_str(), mqtt.port, mqtt.prefix.as_str()), ("10.0.0.2", 1884, "home/pingdown"));
+    assert_eq!(mqtt.credentials, Some(("edge".to_string(), String::new())));
+
+    let cli = Cli::parse_from(vec!["pingdown", "1.1.1.1", "--mqtt", "broker.lan", "--mqtt-prefix", "a/+/b"]);
+    match build_monitor_config(&cli).expect_err("wildcard prefix should fail") {
+        ConfigError::Validation { field_path, .. } => assert_eq!(field_path, "cli --mqtt.prefix"),
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn template_flags_patch_the_file_map_and_report_the_template_name() {
+    let mut file = NamedTempFile::new().expect("temp file");
+    file.write_all(br#"{ "address": ["file.target"], "templates": { "summary": "up {{ up }}", "normal": "ok" } }"#).unwrap();
+    file.flush().unwrap();
+    let path = file.path().to_str().unwrap();
+    let cli = Cli::parse_from(vec!["pingdown", "--config", path, "--template", "normal=fine {{ up }}"]);
+    let templates = build_monitor_config(&cli).unwrap().templates;
+    let names: Vec<&str> = templates.overrides().keys().map(String::as_str).collect();
+    assert_eq!(names, vec!["normal", "summary"]);
+    assert_eq!(templates.overrides()["normal"], "fine {{ up }}");
+
+    let cli = Cli::parse_from(vec!["pingdown", "--config", path, "--template", "banner=hi"]);
+    match build_monitor_config(&cli).expect_err("unknown template should fail") {
+        ConfigError::Validation { field_path, .. } => assert_eq!(field_path, "cli --template.banner"),
+        other => panic!("unexpected error: {}", other),
+    }
+    let mut broken = NamedTempFile::new().expect("temp file");
+    broken.write_all(br#"{ "address": ["file.target"], "templates": { "countdown": "{% if %}" } }"#).unwrap();
+    broken.flush().unwrap();
+    let cli = Cli::parse_from(vec!["pingdown", "--config", broken.path().to_str().unwrap()]);
+    match build_monitor_config(&cli).expect_err("broken template should fail") {
+        ConfigError::Validation { field_path, .. } => assert!(field_path.ends_with("templates.countdown"), "{}", field_path),
+        other => panic!("unexpected error: {}", other),
+    }
+}
@@ -0,0 +1,44 @@
+mod common;
+use common::StubSystem;
+
+use clap::Parser;
+use pingdown::cli::Cli;
+use pingdown::doctor::{exit_code, run_checks, CheckResult, CheckStatus, EXIT_FAIL, EXIT_PASS, EXIT_WARN};
+
+fn status_of(results: &[CheckResult], name: &str) -> CheckStatus {
+    results.iter().find(|result| result.name == name).map(|result| result.status).expect("check present")
+}
+
+#[test]
+fn doctor_checks_config_ping_and_privilege_through_the_system() {
+    let sys = StubSystem::with_static(&[("127.0.0.1", true)]);
+    let cli = Cli::parse_from(vec!["pingdown", "doctor", "1.1.1.1"]);
+
+    let results = run_checks(&cli, &sys);
+    assert_eq!(status_of(&results, "configuration"), CheckStatus::Pass);
+    assert_eq!(status_of(&results, "ping"), CheckStatus::Pass);
+    // IP targets need no resolution
+    assert_eq!(status_of(&results, "dns"), CheckStatus::Pass);
+    // The stub cannot tell whether shutdown is permitted
+    assert_eq!(status_of(&results, "shutdown"), CheckStatus::Warn);
+}
+
+#[test]
+fn invalid_config_fails_and_skips_dns() {
+    let sys = StubSystem::new();
+    let cli = Cli::parse_from(vec!["pingdown", "doctor"]);
+
+    let results = run_checks(&cli, &sys);
+    assert_eq!(status_of(&results, "configuration"), CheckStatus::Fail);
+    assert_eq!(status_of(&results, "ping"), CheckStatus::Fail);
+    assert_eq!(status_of(&results, "dns"), CheckStatus::Warn);
+    assert_eq!(exit_code(&results), EXIT_FAIL);
+}
+
+#[test]
+fn exit_code_reflects_the_worst_result() {
+    let result = |status| CheckResult { name: "x", status, detail: String::new() };
+    assert_eq!(exit_code(&[result(CheckStatus::Pass)]), EXIT_PASS);
+    assert_eq!(exit_code(&[result(CheckStatus::Pass), result(CheckStatus::Warn)]), EXIT_WARN);
+    assert_eq!(exit_code(&[result(CheckStatus::Fail), result(CheckStatus::Warn)]), EXIT_FAIL);
+}
@@ -0,0 +1,55 @@
+use std::time::{Duration, Instant};
+
+use pingdown::flap::{FlapChange, FlapDetector};
+
+#[test]
+fn flapping_starts_once_threshold_transitions_fall_in_window() {
+    let start = Instant::now();
+    let mut flap = FlapDetector::new(Duration::from_secs(60), 3);
+
+    assert_eq!(flap.record_transition(start), FlapChange::Unchanged);
+    assert_eq!(flap.record_transition(start + Duration::from_secs(10)), FlapChange::Unchanged);
+    assert_eq!(flap.record_transition(start + Duration::from_secs(20)), FlapChange::Started);
+    assert!(flap.is_flapping());
+
+    // Further transitions do not re-alert
+    assert_eq!(flap.record_transition(start + Duration::from_secs(30)), FlapChange::Unchanged);
+}
+
+#[test]
+fn spread_out_transitions_do_not_flap() {
+    let start = Instant::now();
+    let mut flap = FlapDetector::new(Duration::from_secs(60), 3);
+
+    for i in 0..5 {
+        let change = flap.record_transition(start + Duration::from_secs(i * 61));
+        assert_eq!(change, FlapChange::Unchanged);
+    }
+    assert!(!flap.is_flapping());
+}
+
+#[test]
+fn flapping_clears_after_a_quiet_window() {
+    let start = Instant::now();
+    let mut flap = FlapDetector::new(Duration::from_secs(60), 2);
+
+    flap.record_transition(start);
+    assert_eq!(flap.record_transition(start + Duration::from_secs(5)), FlapChange::Started);
+
+    assert_eq!(flap.refresh(start + Duration::from_secs(50)), FlapChange::Unchanged);
+    assert!(flap.is_flapping());
+    assert_eq!(flap.refresh(start + Duration::from_secs(70)), FlapChange::Cleared);
+    assert!(!flap.is_flapping());
+}
+
+#[test]
+fn zero_window_disables_detection() {
+    let start = Instant::now();
+    let mut flap = FlapDetector::new(Duration::ZERO, 2);
+
+    for _ in 0..10 {
+        assert_eq!(flap.record_transition(start), FlapChange::Unchanged);
+    }
+    assert!(!flap.enabled());
+    assert!(!flap.is_flapping());
+}
@@ -0,0 +1,68 @@
+mod common;
+use common::StubSystem;
+
+use std::num::NonZeroU32;
+use std::time::Duration;
+
+use chrono::Utc;
+use pingdown::action::{Action, ActionKind, DryRun};
+use pingdown::history::{limit_actions, ActionHistory, ActionRecord};
+use pingdown::runtime::STATE_FILE;
+use pingdown::MonitorConfig;
+
+fn limited(limit: u32, actions: Vec<ActionKind>) -> MonitorConfig {
+    MonitorConfig {
+        targets: vec!["1.1.1.1".to_string()],
+        actions: actions.into_iter().map(Action::new).collect(),
+        action_limit: NonZeroU32::new(limit),
+        ..MonitorConfig::default()
+    }
+}
+
+fn kinds(actions: &[Action]) -> Vec<ActionKind> { actions.iter().map(|action| action.kind.clone()).collect() }
+
+#[test]
+fn power_actions_beyond_the_limit_become_log_only() {
+    let sys = StubSystem::new();
+    let cfg = limited(2, vec![ActionKind::RunCommand("sync".to_string()), ActionKind::Reboot]);
+
+    assert_eq!(kinds(&limit_actions(&cfg, &sys)), kinds(&cfg.actions));
+    assert_eq!(kinds(&limit_actions(&cfg, &sys)), kinds(&cfg.actions));
+    // The third reboot within the window is only logged; other steps still run
+    assert_eq!(
+        kinds(&limit_actions(&cfg, &sys)),
+        vec![ActionKind::RunCommand("sync".to_string()), ActionKind::LogOnly]
+    );
+
+    let state: ActionHistory = serde_json::from_str(&sys.file(STATE_FILE).expect("state file written")).unwrap();
+    assert_eq!(state.power_actions.len(), 2);
+    assert_eq!(state.power_actions[0].action, "reboot");
+}
+
+#[test]
+fn old_records_expire_and_dry_runs_do_not_count() {
+    let sys = StubSystem::new();
+    let now = Utc::now().timestamp();
+    let history = ActionHistory {
+        power_actions: vec![
+            ActionRecord { at: now - 2 * 24 * 60 * 60, action: "reboot".to_string() },
+            ActionRecord { at: now - 60, action: "reboot".to_string() },
+        ],
+    };
+    sys.put_file(STATE_FILE, &serde_json::to_string(&history).unwrap());
+
+    let mut cfg = limited(2, vec![ActionKind::Reboot]);
+    cfg.dry_run = DryRun::Continue;
+    assert_eq!(kinds(&limit_actions(&cfg, &sys)), vec![ActionKind::Reboot]);
+    assert_eq!(sys.file(STATE_FILE).unwrap(), serde_json::to_string(&history).unwrap());
+
+    cfg.dry_run = DryRun::Off;
+    cfg.action_limit_window = Duration::from_secs(30);
+    assert_eq!(kinds(&limit_actions(&cfg, &sys)), vec![ActionKind::Reboot]);
+    assert_eq!(kinds(&limit_actions(&cfg, &sys)), vec![ActionKind::Reboot]);
+    assert_eq!(kinds(&limit_actions(&cfg, &sys)), vec![ActionKind::LogOnly]);
+
+    // A damaged state file never blocks the actions
+    sys.put_file(STATE_FILE, "{ not json");
+    assert_eq!(kinds(&limit_actions(&cfg, &sys)), vec![ActionKind::Reboot]);
+}
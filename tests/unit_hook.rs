@@ -0,0 +1,123 @@
+use std::time::{Duration, Instant};
+
+use pingdown::hook::{run_pre_action_hooks, Hook, HookSpec, OutageContext};
+use pingdown::MonitorConfig;
+
+fn context() -> OutageContext {
+    OutageContext {
+        failing_targets: vec!["1.1.1.1".to_string(), "nas.lan".to_string()],
+        since: Instant::now() - Duration::from_secs(90),
+        retries_used: 3,
+        policy: "retries",
+    }
+}
+
+#[test]
+fn hook_specs_are_validated_per_field() {
+    let spec = HookSpec { command: " sync ".to_string(), timeout: Some(5), veto: true };
+    assert_eq!(Hook::parse(&spec).unwrap(), Hook { command: "sync".to_string(), timeout: Duration::from_secs(5), veto: true });
+
+    let spec = HookSpec { command: "  ".to_string(), ..HookSpec::default() };
+    assert_eq!(Hook::parse(&spec).unwrap_err().0, "command");
+
+    let spec = HookSpec { command: "sync".to_string(), timeout: Some(0), veto: false };
+    assert_eq!(Hook::parse(&spec).unwrap_err().0, "timeout");
+}
+
+#[test]
+fn outage_context_is_exported_as_environment() {
+    let cfg = MonitorConfig::default();
+    let env = context().env(&cfg);
+    let get = |key: &str| env.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+    assert_eq!(get("PINGDOWN_FAILING_TARGETS"), Some("1.1.1.1,nas.lan"));
+    assert_eq!(get("PINGDOWN_OUTAGE_SECS"), Some("90"));
+    assert_eq!(get("PINGDOWN_RETRIES_USED"), Some("3"));
+    assert_eq!(get("PINGDOWN_POLICY"), Some("retries"));
+    assert_eq!(get("PINGDOWN_ACTIONS"), Some("poweroff"));
+    assert!(get("PINGDOWN_OUTAGE_START").is_some());
+}
+
+#[cfg(unix)]
+#[test]
+fn only_veto_hooks_can_stop_the_actions() {
+    use pingdown::system::DefaultSystem;
+
+    let sys = DefaultSystem::new();
+    let mut cfg = MonitorConfig {
+        pre_action_hooks: vec![Hook::new("exit 1", false), Hook::new("test \"$PINGDOWN_POLICY\" = retries", true)],
+        ..MonitorConfig::default()
+    };
+    assert_eq!(run_pre_action_hooks(&cfg, &context(), &sys), None);
+
+    cfg.pre_action_hooks.push(Hook::new("test \"$PINGDOWN_RETRIES_USED\" = 0", true));
+    let reason = run_pre_action_hooks(&cfg, &context(), &sys).expect("the last hook should veto");
+    assert!(reason.contains("PINGDOWN_RETRIES_USED"));
+
+    // A hung hook is killed and never vetoes
+    let mut hung = Hook::new("sleep 5", true);
+    hung.timeout = Duration::from_millis(200);
+    cfg.pre_action_hooks = vec![hung];
+    assert_eq!(run_pre_action_hooks(&cfg, &context(), &sys), None);
+}
+
+#[test]
+fn transition_hooks_name_their_event() {
+    use pingdown::hook::{HookEvent, TransitionHook, TransitionHookSpec};
+
+    let hook = TransitionHook::parse(&TransitionHookSpec::from_cli("emergency:/usr/local/bin/vip-failover --up")).unwrap();
+    assert_eq!(hook, TransitionHook::new(HookEvent::Emergency, "/usr/local/bin/vip-failover --up"));
+
+    let spec = TransitionHookSpec::from_cli("shutdown:true");
+    assert_eq!(TransitionHook::parse(&spec).unwrap_err().0, "on");
+
+    let spec = TransitionHookSpec::from_cli("recovery");
+    assert_eq!(TransitionHook::parse(&spec).unwrap_err().0, "command");
+}
+
+#[test]
+fn transition_context_follows_the_new_state() {
+    use pingdown::hook::{HookEvent, TransitionContext};
+    use pingdown::MonitorState;
+
+    let cfg = MonitorConfig { targets: vec!["1.1.1.1".to_string(), "8.8.8.8".to_string()], ..MonitorConfig::default() };
+    let since = Instant::now() - Duration::from_secs(40);
+    let state = MonitorState::Emergency { failed: 2, recovered: 0, retries_left: 1, since, countdown_from: since };
+    let context = TransitionContext::new(HookEvent::Retry, state, vec!["8.8.8.8".to_string()], &cfg);
+    let env = context.env();
+    let get = |key: &str| env.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+    assert_eq!(get("PINGDOWN_EVENT"), Some("retry"));
+    assert_eq!(get("PINGDOWN_STATE"), Some("emergency"));
+    assert_eq!(get("PINGDOWN_TARGETS"), Some("1.1.1.1,8.8.8.8"));
+    assert_eq!(get("PINGDOWN_FAILING_TARGETS"), Some("8.8.8.8"));
+    assert_eq!(get("PINGDOWN_FAILED_CYCLES"), Some("2"));
+    assert_eq!(get("PINGDOWN_RETRIES_LEFT"), Some("1"));
+    assert_eq!(get("PINGDOWN_OUTAGE_SECS"), Some("40"));
+
+    let recovered = MonitorState::Recovered { outage: Duration::from_secs(75) };
+    let context = TransitionContext::new(HookEvent::Recovery, recovered, Vec::new(), &cfg);
+    assert_eq!((context.outage_secs, context.retries_left), (75, None));
+}
+
+#[cfg(unix)]
+#[test]
+fn json_hooks_get_the_context_on_stdin() {
+    use pingdown::hook::{run_transition_hooks, HookEvent, TransitionHook};
+    use pingdown::system::DefaultSystem;
+    use pingdown::MonitorState;
+
+    let out = tempfile::NamedTempFile::new().unwrap();
+    let mut hook = TransitionHook::new(HookEvent::Recovery, format!("cat > {}", out.path().display()));
+    hook.json = true;
+    let cfg = MonitorConfig { transition_hooks: vec![hook], ..MonitorConfig::default() };
+
+    let state = MonitorState::Recovered { outage: Duration::from_secs(12) };
+    // Hooks for other events are not run
+    run_transition_hooks(&cfg, HookEvent::Emergency, state, &[], &DefaultSystem::new());
+    assert_eq!(std::fs::read_to_string(out.path()).unwrap(), "");
+
+    run_transition_hooks(&cfg, HookEvent::Recovery, state, &[], &DefaultSystem::new());
+    let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(out.path()).unwrap()).unwrap();
+    assert_eq!(json["event"], "recovery");
+    assert_eq!(json["state"], "recovered");
+    assert_eq!(json["outage_secs"], 12);
+}
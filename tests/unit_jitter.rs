@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use pingdown::jitter::{Jitter, JitterSpread};
+
+#[test]
+fn same_seed_yields_same_delays() {
+    let mut a = Jitter::new(JitterSpread::Percent(20), 42);
+    let mut b = Jitter::new(JitterSpread::Percent(20), 42);
+    let base = Duration::from_secs(60);
+
+    let first: Vec<Duration> = (0..16).map(|_| a.apply(base)).collect();
+    let second: Vec<Duration> = (0..16).map(|_| b.apply(base)).collect();
+    assert_eq!(first, second);
+    // The spread actually moves the delays around
+    assert!(first.iter().any(|d| *d != first[0]));
+}
+
+#[test]
+fn delays_stay_within_the_spread() {
+    let base = Duration::from_secs(60);
+
+    let mut pct = Jitter::new(JitterSpread::Percent(10), 7);
+    for _ in 0..200 {
+        let delay = pct.apply(base);
+        assert!(delay >= Duration::from_secs(54) && delay <= Duration::from_secs(66), "{:?}", delay);
+    }
+
+    let mut fixed = Jitter::new(JitterSpread::Fixed(Duration::from_secs(5)), 7);
+    for _ in 0..200 {
+        let delay = fixed.apply(base);
+        assert!(delay >= Duration::from_secs(55) && delay <= Duration::from_secs(65), "{:?}", delay);
+    }
+}
+
+#[test]
+fn no_spread_and_short_intervals_are_handled() {
+    let mut none = Jitter::new(JitterSpread::None, 1);
+    assert_eq!(none.apply(Duration::from_secs(30)), Duration::from_secs(30));
+
+    // A spread wider than the interval never produces a sub-second sleep
+    let mut wide = Jitter::new(JitterSpread::Fixed(Duration::from_secs(10)), 1);
+    for _ in 0..50 {
+        assert!(wide.apply(Duration::from_secs(2)) >= Duration::from_secs(1));
+    }
+
+    // Spreads too wide to count in milliseconds saturate instead of panicking
+    let mut huge = Jitter::new(JitterSpread::Fixed(Duration::from_secs(u64::MAX)), 1);
+    for _ in 0..50 {
+        assert!(huge.apply(Duration::from_secs(60)) >= Duration::from_secs(1));
+    }
+    assert!(huge.offset(Duration::MAX) <= Duration::MAX);
+}
+
+#[test]
+fn initial_offset_is_bounded() {
+    let mut jitter = Jitter::new(JitterSpread::None, 9);
+    assert_eq!(jitter.offset(Duration::ZERO), Duration::ZERO);
+    for _ in 0..100 {
+        assert!(jitter.offset(Duration::from_secs(30)) <= Duration::from_secs(30));
+    }
+}
@@ -0,0 +1,152 @@
+#![cfg(unix)]
+
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use pingdown::action::{ActionKind, ActionResult};
+use pingdown::logind::{command_line, power_action_on};
+use zbus::blocking::{connection, Connection};
+use zbus::message::{Flags, Header};
+use zbus::{fdo, interface};
+
+/// Private session bus, killed when dropped.
+struct Bus {
+    daemon: Child,
+    address: String,
+}
+
+impl Bus {
+    /// `None` when dbus-daemon is not installed.
+    fn start() -> Option<Self> {
+        let mut daemon = Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let mut address = String::new();
+        BufReader::new(daemon.stdout.take()?).read_line(&mut address).ok()?;
+        Some(Self { daemon, address: address.trim().to_string() })
+    }
+
+    fn connect(&self) -> Connection {
+        connection::Builder::address(self.address.as_str())
+            .unwrap()
+            .method_timeout(Duration::from_secs(5))
+            .build()
+            .unwrap()
+    }
+}
+
+impl Drop for Bus {
+    fn drop(&mut self) {
+        let _ = self.daemon.kill();
+        let _ = self.daemon.wait();
+    }
+}
+
+/// Stands in for logind: every Can* query answers `can`, power calls are logged with
+/// their arguments and whether interactive authorization was allowed. Reboot only has
+/// the pre-flags method, like older systemd.
+struct StandIn {
+    can: Arc<Mutex<String>>,
+    calls: Arc<Mutex<Vec<String>>>,
+}
+
+impl StandIn {
+    fn log(&self, header: &Header<'_>, call: String) {
+        let interactive = header.primary().flags().contains(Flags::AllowInteractiveAuth);
+        self.calls.lock().unwrap().push(format!("{} interactive={}", call, interactive));
+    }
+}
+
+#[interface(name = "org.freedesktop.login1.Manager")]
+impl StandIn {
+    fn can_power_off(&self) -> String { self.can.lock().unwrap().clone() }
+    fn can_reboot(&self) -> String { self.can.lock().unwrap().clone() }
+    fn can_suspend(&self) -> String { self.can.lock().unwrap().clone() }
+
+    fn power_off_with_flags(&self, flags: u64, #[zbus(header)] header: Header<'_>) {
+        self.log(&header, format!("PowerOffWithFlags({})", flags));
+    }
+
+    fn reboot(&self, interactive: bool, #[zbus(header)] header: Header<'_>) {
+        self.log(&header, format!("Reboot({})", interactive));
+    }
+
+    fn suspend_with_flags(&self, _flags: u64) -> fdo::Result<()> {
+        Err(fdo::Error::AccessDenied("Operation inhibited by \"backup\"".to_string()))
+    }
+}
+
+#[test]
+fn logind_replies_and_calls_are_interpreted() {
+    assert_eq!(
+        command_line(&ActionKind::Reboot).as_deref(),
+        Some("org.freedesktop.login1.Manager.RebootWithFlags(1)")
+    );
+    assert_eq!(command_line(&ActionKind::RunCommand("true".to_string())), None);
+
+    let Some(bus) = Bus::start() else {
+        eprintln!("skipping: dbus-daemon is not available");
+        return;
+    };
+    let client = bus.connect();
+    // Nothing owns the logind name yet
+    assert_eq!(power_action_on(&client, &ActionKind::Poweroff), None);
+
+    let can = Arc::new(Mutex::new("yes".to_string()));
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let stand_in = StandIn { can: can.clone(), calls: calls.clone() };
+    let _service = connection::Builder::address(bus.address.as_str())
+        .unwrap()
+        .name("org.freedesktop.login1")
+        .unwrap()
+        .serve_at("/org/freedesktop/login1", stand_in)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let (result, attempts) = power_action_on(&client, &ActionKind::Poweroff).unwrap();
+    assert_eq!(result, ActionResult::Ok);
+    assert_eq!(attempts.len(), 1);
+    assert_eq!(attempts[0].command, "org.freedesktop.login1.Manager.PowerOffWithFlags(1)");
+
+    // Without RebootWithFlags the old method is called, still non-interactive
+    let (result, attempts) = power_action_on(&client, &ActionKind::Reboot).unwrap();
+    assert_eq!(result, ActionResult::Ok);
+    assert_eq!(attempts.len(), 2);
+    assert_eq!(attempts[0].status.as_deref(), Some("org.freedesktop.DBus.Error.UnknownMethod"));
+    assert_eq!(
+        *calls.lock().unwrap(),
+        vec!["PowerOffWithFlags(1) interactive=false", "Reboot(false) interactive=false"]
+    );
+
+    match power_action_on(&client, &ActionKind::Suspend) {
+        Some((ActionResult::Failed(reason), attempts)) => {
+            assert_eq!(reason, "logind: Operation inhibited by \"backup\"");
+            assert_eq!(attempts[0].status.as_deref(), Some("org.freedesktop.DBus.Error.AccessDenied"));
+        }
+        other => panic!("expected an inhibited failure, got {:?}", other),
+    }
+
+    for answer in ["challenge", "no"] {
+        *can.lock().unwrap() = answer.to_string();
+        match power_action_on(&client, &ActionKind::Poweroff) {
+            Some((ActionResult::Failed(reason), attempts)) => {
+                assert!(reason.contains(&format!("'{}'", answer)), "{}", reason);
+                assert_eq!(attempts.len(), 1);
+            }
+            other => panic!("expected a polkit refusal, got {:?}", other),
+        }
+    }
+    // Neither refusal reached the power call
+    assert_eq!(calls.lock().unwrap().len(), 2);
+
+    // A method logind does not answer at all counts as unreachable
+    *can.lock().unwrap() = "yes".to_string();
+    assert_eq!(power_action_on(&client, &ActionKind::Hibernate), None);
+    assert_eq!(power_action_on(&client, &ActionKind::RunCommand("true".to_string())), None);
+}
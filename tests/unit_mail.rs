@@ -0,0 +1,145 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use pingdown::mail::{render, Smtp, SmtpSpec};
+use pingdown::notify::{Notification, NotifyEvent};
+use pingdown::ping::CycleReport;
+use pingdown::state::MonitorState;
+use pingdown::template::Templates;
+use pingdown::MonitorConfig;
+
+/// Minimal SMTP sink: accepts every command and passes back the envelope and DATA of each message.
+fn sink() -> (u16, mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let Ok((stream, _)) = listener.accept() else { return };
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut writer = stream;
+        writer.write_all(b"220 sink ESMTP\r\n").unwrap();
+        let mut transcript = String::new();
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap() == 0 {
+                break;
+            }
+            let command = line.to_ascii_uppercase();
+            let reply: &[u8] = if command.starts_with("EHLO") || command.starts_with("HELO") {
+                b"250 sink\r\n"
+            } else if command.starts_with("DATA") {
+                writer.write_all(b"354 go ahead\r\n").unwrap();
+                loop {
+                    let mut data = String::new();
+                    reader.read_line(&mut data).unwrap();
+                    if data == ".\r\n" {
+                        break;
+                    }
+                    transcript.push_str(&data);
+                }
+                b"250 queued\r\n"
+            } else if command.starts_with("QUIT") {
+                writer.write_all(b"221 bye\r\n").unwrap();
+                break;
+            } else {
+                transcript.push_str(&line);
+                b"250 ok\r\n"
+            };
+            writer.write_all(reply).unwrap();
+        }
+        let _ = tx.send(transcript);
+    });
+    (port, rx)
+}
+
+fn spec(server: &str) -> SmtpSpec {
+    SmtpSpec {
+        server: server.to_string(),
+        from: "pingdown@example.com".to_string(),
+        to: vec!["ops@example.com".to_string(), "Night Shift <oncall@example.com>".to_string()],
+        ..SmtpSpec::default()
+    }
+}
+
+fn outage(event: NotifyEvent, state: MonitorState) -> Notification {
+    let cfg = MonitorConfig { targets: vec!["gw".to_string(), "1.1.1.1".to_string()], ..MonitorConfig::default() };
+    let report = CycleReport {
+        ok: false,
+        succeeds: 0,
+        failures: 1,
+        down: vec!["gw".to_string()],
+        unreachable: vec![("1.1.1.1".to_string(), "gw".to_string())],
+        ..CycleReport::default()
+    };
+    Notification::new(event, state, &cfg, Some(&report), "box")
+}
+
+#[test]
+fn smtp_settings_are_validated_per_field() {
+    let smtp = Smtp::parse(&spec("relay.internal")).unwrap();
+    assert_eq!((smtp.server.as_str(), smtp.port), ("relay.internal", 25));
+    assert_eq!(smtp.to.len(), 2);
+
+    let smtp = Smtp::parse(&SmtpSpec { starttls: true, ..spec("relay.internal") }).unwrap();
+    assert_eq!(smtp.port, 587);
+    assert_eq!(Smtp::parse(&spec("relay.internal:2525")).unwrap().port, 2525);
+    assert_eq!(Smtp::parse(&spec("relay.internal:smtp")).unwrap_err().0, "server");
+
+    // IPv6 relays: bracketed with a port, or bare without one
+    let smtp = Smtp::parse(&spec("[2001:db8::1]:2525")).unwrap();
+    assert_eq!((smtp.server.as_str(), smtp.port), ("2001:db8::1", 2525));
+    let smtp = Smtp::parse(&spec("::1")).unwrap();
+    assert_eq!((smtp.server.as_str(), smtp.port), ("::1", 25));
+    assert_eq!(Smtp::parse(&spec("[::1]")).unwrap().server, "::1");
+    assert_eq!(Smtp::parse(&spec("[::1]2525")).unwrap_err().0, "server");
+
+    assert_eq!(Smtp::parse(&spec("")).unwrap_err().0, "server");
+    assert_eq!(Smtp::parse(&SmtpSpec { from: "not an address".to_string(), ..spec("relay") }).unwrap_err().0, "from");
+    assert_eq!(Smtp::parse(&SmtpSpec { to: Vec::new(), ..spec("relay") }).unwrap_err().0, "to");
+    let user_only = SmtpSpec { username: Some("alerts".to_string()), ..spec("relay") };
+    assert_eq!(Smtp::parse(&user_only).unwrap_err().0, "password");
+}
+
+#[test]
+fn alerts_carry_the_outage_context() {
+    let templates = Templates::default();
+    let now = std::time::Instant::now();
+    let state = MonitorState::Emergency { failed: 3, recovered: 0, retries_left: 2, since: now, countdown_from: now };
+    let (subject, body) = render(&outage(NotifyEvent::Emergency, state), &templates).unwrap();
+    assert_eq!(subject, "[pingdown] box: connectivity lost");
+    assert!(body.starts_with("Connection lost after 3 failed cycle(s). 2 tries left before the actions run.\n"), "{}", body);
+    assert!(body.contains("Root cause: gw down.\n"), "{}", body);
+    assert!(body.contains("Failing targets: gw, 1.1.1.1\n"), "{}", body);
+    assert!(body.contains("  1.1.1.1 unreachable (via gw)\n"), "{}", body);
+
+    let state = MonitorState::Recovered { outage: Duration::from_secs(95) };
+    let (subject, _) = render(&outage(NotifyEvent::Recovery, state), &templates).unwrap();
+    assert_eq!(subject, "[pingdown] box: connectivity restored after 95s");
+
+    let (subject, _) = render(&outage(NotifyEvent::Action, MonitorState::Acting { failed: 5, since: now }), &templates).unwrap();
+    assert_eq!(subject, "[pingdown] box: running poweroff");
+    assert_eq!(render(&outage(NotifyEvent::Startup, MonitorState::Normal), &templates), None);
+
+    let custom = [("mail-subject".to_string(), "{{ event | upper }} on {{ host }} ({{ root_causes | join(\",\") }})".to_string())];
+    let templates = Templates::new(custom.into_iter().collect()).unwrap();
+    let (subject, body) = render(&outage(NotifyEvent::Recovery, MonitorState::Normal), &templates).unwrap();
+    assert_eq!(subject, "RECOVERY on box (gw)");
+    assert!(body.starts_with("Reconnected after an outage of 0s.\n"), "{}", body);
+}
+
+#[test]
+fn alerts_are_delivered_to_a_local_relay() {
+    let (port, transcripts) = sink();
+    let smtp = Smtp::parse(&spec(&format!("127.0.0.1:{}", port))).unwrap();
+    smtp.send("[pingdown] box: connectivity lost", "Connection lost after 3 failed cycle(s).\n").unwrap();
+
+    let transcript = transcripts.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(transcript.contains("MAIL FROM:<pingdown@example.com>"), "{}", transcript);
+    assert!(transcript.contains("RCPT TO:<ops@example.com>"), "{}", transcript);
+    assert!(transcript.contains("RCPT TO:<oncall@example.com>"), "{}", transcript);
+    assert!(transcript.contains("Subject: [pingdown] box: connectivity lost"), "{}", transcript);
+    assert!(transcript.contains("Connection lost after 3 failed cycle(s)."), "{}", transcript);
+}
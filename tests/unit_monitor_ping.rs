@@ -6,7 +6,7 @@ use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use pingdown::MonitorConfig;
-use pingdown::ping::check_status;
+use pingdown::ping::{check_status, parse_rtt};
 use pingdown::runtime::{add_one, MetricEvent, Metrics};
 
 fn base_config(strict: bool, addrs: Vec<&str>) -> MonitorConfig {
@@ -18,8 +18,7 @@ fn base_config(strict: bool, addrs: Vec<&str>) -> MonitorConfig {
         emergency_retries: NonZeroU32::new(1).unwrap(),
         quiet: true,
         status_only: true,
-        progress: false,
-        verbose: 0,
+        ..MonitorConfig::default()
     }
 }
 
@@ -63,3 +62,90 @@ fn metrics_are_incremented() {
     let m = metrics.lock().unwrap();
     assert_eq!(m.total_normal_loop_times, 1);
 }
+
+#[test]
+fn cycle_history_reports_availability_by_cycles_and_probes() {
+    use pingdown::config::AvailabilityBasis;
+    use pingdown::runtime::{CycleHistory, CycleSample};
+    use std::time::Instant;
+
+    let start = Instant::now();
+    let mut history = CycleHistory::new(Duration::from_secs(100), start);
+    assert_eq!(history.availability(AvailabilityBasis::Cycles), None);
+
+    let outcomes = [(true, 2, 0), (false, 0, 2), (true, 1, 1), (true, 2, 0)];
+    for (i, (ok, succeeds, failures)) in outcomes.into_iter().enumerate() {
+        let at = start + Duration::from_secs(10 * i as u64);
+        history.record(CycleSample { at, ok, succeeds, failures });
+    }
+
+    assert_eq!(history.availability(AvailabilityBasis::Cycles), Some(75.0));
+    assert_eq!(history.availability(AvailabilityBasis::Probes), Some(62.5));
+    assert!(!history.covers_window(start + Duration::from_secs(30)));
+    assert!(history.covers_window(start + Duration::from_secs(100)));
+}
+
+#[test]
+fn cycle_history_evicts_samples_outside_the_window() {
+    use pingdown::config::AvailabilityBasis;
+    use pingdown::runtime::{CycleHistory, CycleSample};
+    use std::time::Instant;
+
+    let start = Instant::now();
+    let mut history = CycleHistory::new(Duration::from_secs(60), start);
+    history.record(CycleSample { at: start, ok: false, succeeds: 0, failures: 1 });
+    history.record(CycleSample { at: start + Duration::from_secs(30), ok: true, succeeds: 1, failures: 0 });
+    history.record(CycleSample { at: start + Duration::from_secs(90), ok: true, succeeds: 1, failures: 0 });
+
+    assert_eq!(history.len(), 2);
+    assert_eq!(history.availability(AvailabilityBasis::Cycles), Some(100.0));
+}
+
+#[test]
+fn children_of_a_down_parent_are_not_probed_or_counted() {
+    use pingdown::ping::check_targets;
+
+    let sys = StubSystem::with_static(&[("router", false), ("a", true), ("b", true)]);
+    let mut cfg = base_config(false, vec!["router", "a", "b"]);
+    cfg.dependencies.insert("a".to_string(), "router".to_string());
+    cfg.dependencies.insert("b".to_string(), "router".to_string());
+    let metrics = Arc::new(Mutex::new(Metrics::new()));
+
+    let report = check_targets(&cfg, &metrics, &sys);
+    assert!(!report.ok);
+    assert_eq!((report.succeeds, report.failures), (0, 1));
+    assert_eq!(report.unreachable.len(), 2);
+    assert_eq!(report.root_causes(), vec!["router"]);
+    assert_eq!(metrics.lock().unwrap().total_failures, 1);
+}
+
+#[test]
+fn gateway_parent_resolves_through_the_system() {
+    use pingdown::ping::check_targets;
+
+    let mut sys = StubSystem::with_static(&[("192.168.1.1", true), ("8.8.8.8", false)]);
+    sys.gateway = Some("192.168.1.1".to_string());
+    let mut cfg = base_config(true, vec!["8.8.8.8"]);
+    cfg.dependencies.insert("8.8.8.8".to_string(), "@gateway".to_string());
+    let metrics = Arc::new(Mutex::new(Metrics::new()));
+
+    // Gateway up: the child is probed and fails on its own
+    let report = check_targets(&cfg, &metrics, &sys);
+    assert_eq!(report.failures, 1);
+    assert!(report.unreachable.is_empty());
+
+    // No gateway at all: the child is unreachable, and strict mode still fails the cycle
+    sys.gateway = None;
+    let report = check_targets(&cfg, &metrics, &sys);
+    assert!(!report.ok);
+    assert_eq!(report.failures, 0);
+    assert_eq!(report.unreachable, vec![("8.8.8.8".to_string(), "@gateway".to_string())]);
+}
+
+#[test]
+fn round_trip_times_are_read_from_ping_output() {
+    let unix = "64 bytes from 1.1.1.1: icmp_seq=1 ttl=57 time=12.3 ms";
+    assert_eq!(parse_rtt(unix), Some(Duration::from_secs_f64(0.0123)));
+    assert_eq!(parse_rtt("Reply from 1.1.1.1: bytes=32 time<1ms TTL=64"), Some(Duration::from_millis(1)));
+    assert_eq!(parse_rtt("Request timed out."), None);
+}
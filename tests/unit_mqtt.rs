@@ -0,0 +1,146 @@
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use pingdown::mqtt::{Mqtt, MqttPublisher, MqttSpec};
+use pingdown::ping::CycleReport;
+use pingdown::state::MonitorState;
+
+/// `(topic, payload, retained)` as seen by the broker; the Last Will arrives as topic `will:<topic>`.
+type Message = (String, String, bool);
+
+fn read_string(body: &[u8], at: &mut usize) -> String {
+    let len = u16::from_be_bytes([body[*at], body[*at + 1]]) as usize;
+    let text = String::from_utf8_lossy(&body[*at + 2..*at + 2 + len]).into_owned();
+    *at += 2 + len;
+    text
+}
+
+/// Just enough of an MQTT 3.1.1 broker to accept one client and record what it sends.
+fn broker() -> (u16, mpsc::Receiver<Message>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let (mut stream, _): (TcpStream, _) = listener.accept().unwrap();
+        loop {
+            let mut header = [0u8; 1];
+            if stream.read_exact(&mut header).is_err() {
+                return;
+            }
+            let (mut len, mut shift) = (0usize, 0);
+            loop {
+                let mut byte = [0u8; 1];
+                stream.read_exact(&mut byte).unwrap();
+                len |= ((byte[0] & 0x7f) as usize) << shift;
+                shift += 7;
+                if byte[0] & 0x80 == 0 {
+                    break;
+                }
+            }
+            let mut body = vec![0; len];
+            stream.read_exact(&mut body).unwrap();
+            match header[0] >> 4 {
+                1 => {
+                    let flags = body[7];
+                    let mut at = 10;
+                    read_string(&body, &mut at);
+                    if flags & 0x04 != 0 {
+                        let topic = read_string(&body, &mut at);
+                        let payload = read_string(&body, &mut at);
+                        let _ = tx.send((format!("will:{}", topic), payload, flags & 0x20 != 0));
+                    }
+                    stream.write_all(&[0x20, 0x02, 0x00, 0x00]).unwrap();
+                }
+                3 => {
+                    let qos = (header[0] >> 1) & 0x03;
+                    let mut at = 0;
+                    let topic = read_string(&body, &mut at);
+                    if qos > 0 {
+                        stream.write_all(&[0x40, 0x02, body[at], body[at + 1]]).unwrap();
+                        at += 2;
+                    }
+                    let payload = String::from_utf8_lossy(&body[at..]).into_owned();
+                    let _ = tx.send((topic, payload, header[0] & 0x01 != 0));
+                }
+                12 => stream.write_all(&[0xd0, 0x00]).unwrap(),
+                _ => {}
+            }
+        }
+    });
+    (port, rx)
+}
+
+fn mqtt(broker: &str) -> Mqtt {
+    let spec = MqttSpec { broker: broker.to_string(), prefix: Some("site/box".to_string()), ..MqttSpec::default() };
+    Mqtt::parse(&spec).unwrap()
+}
+
+fn report() -> CycleReport {
+    CycleReport {
+        ok: false,
+        succeeds: 1,
+        failures: 1,
+        down: vec!["gw".to_string()],
+        unreachable: vec![("dns/primary".to_string(), "gw".to_string())],
+        ..CycleReport::default()
+    }
+}
+
+#[test]
+fn broker_settings_are_validated_per_field() {
+    let config = mqtt("broker.lan");
+    assert_eq!((config.host.as_str(), config.port), ("broker.lan", 1883));
+    assert_eq!(config.status_topic(), "site/box/status");
+    assert_eq!(config.target_topic("dns/primary"), "site/box/targets/dns_primary");
+    assert_eq!(mqtt("broker.lan:8883").port, 8883);
+
+    let default_prefix = Mqtt::parse(&MqttSpec { broker: "broker.lan".to_string(), ..MqttSpec::default() }).unwrap();
+    assert!(default_prefix.prefix.starts_with("pingdown/"), "{}", default_prefix.prefix);
+
+    assert_eq!(Mqtt::parse(&MqttSpec::default()).unwrap_err().0, "broker");
+    let wildcard = MqttSpec { broker: "b".to_string(), prefix: Some("home/#".to_string()), ..MqttSpec::default() };
+    assert_eq!(Mqtt::parse(&wildcard).unwrap_err().0, "prefix");
+}
+
+#[test]
+fn state_and_targets_are_published_retained_with_a_last_will() {
+    let (port, messages) = broker();
+    let publisher = MqttPublisher::start(&mqtt(&format!("127.0.0.1:{}", port)));
+    let now = Instant::now();
+    let state = MonitorState::Emergency { failed: 1, recovered: 0, retries_left: 3, since: now, countdown_from: now };
+    publisher.publish_cycle(state, &["gw".to_string(), "dns/primary".to_string(), "1.1.1.1".to_string()], &report());
+
+    let mut received = Vec::new();
+    while received.len() < 6 {
+        received.push(messages.recv_timeout(Duration::from_secs(5)).expect("broker got too few messages"));
+    }
+    let find = |topic: &str| received.iter().find(|(t, _, _)| t == topic).unwrap_or_else(|| panic!("{} missing", topic));
+    assert_eq!(find("will:site/box/status"), &("will:site/box/status".to_string(), "offline".to_string(), true));
+    assert_eq!(find("site/box/status").1, "online");
+    assert_eq!(find("site/box/targets/gw").1, "down");
+    assert_eq!(find("site/box/targets/dns_primary").1, "unreachable");
+    assert_eq!(find("site/box/targets/1.1.1.1").1, "up");
+    let (_, state, retained) = find("site/box/state");
+    assert!(retained);
+    let state: serde_json::Value = serde_json::from_str(state).unwrap();
+    assert_eq!(state["state"], "emergency");
+    assert_eq!(state["failing_targets"], serde_json::json!(["gw", "dns/primary"]));
+    assert!(received.iter().all(|(_, _, retained)| *retained));
+}
+
+#[test]
+fn an_unreachable_broker_never_blocks_publishing() {
+    let closed = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().port()
+    };
+    let publisher = MqttPublisher::start(&mqtt(&format!("127.0.0.1:{}", closed)));
+    let started = Instant::now();
+    for _ in 0..200 {
+        publisher.publish_cycle(MonitorState::Normal, &["gw".to_string()], &report());
+    }
+    assert!(started.elapsed() < Duration::from_secs(1), "{:?}", started.elapsed());
+}
@@ -0,0 +1,55 @@
+use chrono::Weekday;
+
+use pingdown::schedule::{TimeWindow, TimeWindowSpec};
+
+fn window(days: &[&str], start: &str, end: &str) -> TimeWindow {
+    let spec = TimeWindowSpec {
+        days: days.iter().map(|d| d.to_string()).collect(),
+        start: start.to_string(),
+        end: end.to_string(),
+    };
+    TimeWindow::parse(&spec).expect("valid window")
+}
+
+#[test]
+fn same_day_window_respects_bounds_and_days() {
+    let w = window(&["mon", "wed"], "01:00", "05:00");
+    assert!(w.contains(Weekday::Mon, 60));
+    assert!(w.contains(Weekday::Wed, 4 * 60 + 59));
+    assert!(!w.contains(Weekday::Mon, 5 * 60)); // end is exclusive
+    assert!(!w.contains(Weekday::Tue, 2 * 60));
+    assert_eq!(w.to_string(), "mon,wed 01:00-05:00");
+}
+
+#[test]
+fn overnight_window_belongs_to_its_start_day() {
+    let w = window(&["sat"], "22:00", "02:00");
+    assert!(w.contains(Weekday::Sat, 23 * 60));
+    assert!(w.contains(Weekday::Sun, 60));
+    assert!(!w.contains(Weekday::Sat, 60));
+    assert!(!w.contains(Weekday::Sun, 23 * 60));
+}
+
+#[test]
+fn empty_days_means_every_day() {
+    let w = window(&[], "00:00", "24:00");
+    for day in [Weekday::Mon, Weekday::Thu, Weekday::Sun] {
+        assert!(w.contains(day, 0));
+        assert!(w.contains(day, 24 * 60 - 1));
+    }
+}
+
+#[test]
+fn invalid_entries_name_the_field() {
+    let spec = |days: &[&str], start: &str, end: &str| TimeWindowSpec {
+        days: days.iter().map(|d| d.to_string()).collect(),
+        start: start.to_string(),
+        end: end.to_string(),
+    };
+    assert_eq!(TimeWindow::parse(&spec(&["funday"], "01:00", "02:00")).unwrap_err().0, "days");
+    assert_eq!(TimeWindow::parse(&spec(&[], "25:00", "02:00")).unwrap_err().0, "start");
+    assert_eq!(TimeWindow::parse(&spec(&[], "01:00", "02:75")).unwrap_err().0, "end");
+    assert_eq!(TimeWindow::parse(&spec(&[], "01:00", "01:00")).unwrap_err().0, "end");
+    assert_eq!(TimeWindow::parse(&spec(&[], "99999999:00", "02:00")).unwrap_err().0, "start");
+    assert_eq!(TimeWindow::parse(&spec(&[], "01:00", "24:01")).unwrap_err().0, "end");
+}
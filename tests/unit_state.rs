@@ -0,0 +1,160 @@
+use std::time::{Duration, Instant};
+
+use pingdown::config::DecisionPolicy;
+use pingdown::state::{transition, CycleInput, Hold, MonitorState, StatePolicy};
+
+fn policy(fail: u32, recover: u32, retries: u32) -> StatePolicy {
+    StatePolicy {
+        fail_threshold: fail,
+        recover_threshold: recover,
+        emergency_retries: retries,
+        max_outage: None,
+        countdown: None,
+        decision: DecisionPolicy::Consecutive,
+    }
+}
+
+fn run(policy: &StatePolicy, start: Instant, results: &[bool]) -> Vec<MonitorState> {
+    let mut state = MonitorState::Normal;
+    results
+        .iter()
+        .enumerate()
+        .map(|(i, &ok)| {
+            let at = start + Duration::from_secs(i as u64 * 10);
+            state = transition(state, CycleInput { ok, at, hold: None, availability_low: false }, policy).state;
+            state
+        })
+        .collect()
+}
+
+#[test]
+fn action_fires_after_fail_threshold_plus_retries_failed_checks() {
+    let start = Instant::now();
+    let states = run(&policy(2, 1, 3), start, &[false; 5]);
+    let names: Vec<&str> = states.iter().map(MonitorState::name).collect();
+    assert_eq!(names, ["suspect", "emergency", "emergency", "emergency", "acting"]);
+    assert_eq!(states[4], MonitorState::Acting { failed: 5, since: start });
+}
+
+#[test]
+fn single_retry_acts_on_first_emergency_failure() {
+    let states = run(&policy(1, 1, 1), Instant::now(), &[false, false]);
+    assert_eq!(states[0].name(), "emergency");
+    assert_eq!(states[1].name(), "acting");
+}
+
+#[test]
+fn success_while_suspect_returns_to_normal() {
+    let states = run(&policy(3, 1, 1), Instant::now(), &[false, false, true, false]);
+    assert_eq!(states[2], MonitorState::Normal);
+    assert!(matches!(states[3], MonitorState::Suspect { failed: 1, .. }));
+}
+
+#[test]
+fn successes_do_not_consume_retries() {
+    let states = run(&policy(1, 2, 2), Instant::now(), &[false, true, false, true, true]);
+    assert!(matches!(states[1], MonitorState::Emergency { retries_left: 2, recovered: 1, .. }));
+    assert!(matches!(states[2], MonitorState::Emergency { retries_left: 1, recovered: 0, .. }));
+    assert!(matches!(states[3], MonitorState::Emergency { retries_left: 1, recovered: 1, .. }));
+    assert_eq!(states[4], MonitorState::Recovered { outage: Duration::from_secs(40) });
+}
+
+#[test]
+fn recovered_is_reported_once_then_normal() {
+    let states = run(&policy(1, 1, 2), Instant::now(), &[false, true, true]);
+    assert!(matches!(states[1], MonitorState::Recovered { .. }));
+    assert_eq!(states[2], MonitorState::Normal);
+}
+
+#[test]
+fn max_outage_ignores_retry_count() {
+    let start = Instant::now();
+    let policy = StatePolicy { max_outage: Some(Duration::from_secs(30)), ..policy(1, 1, 1) };
+    let states = run(&policy, start, &[false, false, false, false]);
+    // Countdown starts at the first failure (t=0); t=10 and t=20 are within the limit
+    assert_eq!(states[2].name(), "emergency");
+    assert_eq!(states[3], MonitorState::Acting { failed: 4, since: start });
+}
+
+#[test]
+fn restart_hold_starts_a_fresh_countdown() {
+    let start = Instant::now();
+    let p = policy(1, 1, 2);
+    let state = MonitorState::Emergency { failed: 3, recovered: 0, retries_left: 1, since: start, countdown_from: start };
+    let at = start + Duration::from_secs(20);
+    let step = transition(state, CycleInput { ok: false, at, hold: Some(Hold::Restart), availability_low: false }, &p);
+    assert_eq!(step.held, Some(Hold::Restart));
+    assert_eq!(
+        step.state,
+        MonitorState::Emergency { failed: 4, recovered: 0, retries_left: 2, since: start, countdown_from: at }
+    );
+}
+
+#[test]
+fn wait_hold_keeps_the_action_due() {
+    let start = Instant::now();
+    let p = policy(1, 1, 1);
+    let state = MonitorState::Emergency { failed: 1, recovered: 0, retries_left: 1, since: start, countdown_from: start };
+    let held = transition(state, CycleInput { ok: false, at: start, hold: Some(Hold::Wait), availability_low: false }, &p);
+    assert_eq!(held.held, Some(Hold::Wait));
+    assert!(matches!(held.state, MonitorState::Emergency { retries_left: 0, .. }));
+
+    // Once the hold lifts, the next failed check acts straight away
+    let step = transition(held.state, CycleInput { ok: false, at: start, hold: None, availability_low: false }, &p);
+    assert_eq!(step.state, MonitorState::Acting { failed: 3, since: start });
+}
+
+#[test]
+fn countdown_delays_the_action_until_the_deadline() {
+    let start = Instant::now();
+    let p = StatePolicy { countdown: Some(Duration::from_secs(15)), ..policy(1, 1, 1) };
+    let states = run(&p, start, &[false, false, false, false]);
+    let deadline = start + Duration::from_secs(25);
+    assert_eq!(states[1], MonitorState::Countdown { failed: 2, since: start, deadline });
+    // t=20 is still before the deadline, t=30 is past it
+    assert_eq!(states[2], MonitorState::Countdown { failed: 3, since: start, deadline });
+    assert_eq!(states[3], MonitorState::Acting { failed: 4, since: start });
+}
+
+#[test]
+fn success_cancels_the_countdown() {
+    let start = Instant::now();
+    let p = StatePolicy { countdown: Some(Duration::from_secs(60)), ..policy(1, 1, 1) };
+    let states = run(&p, start, &[false, false, true]);
+    assert_eq!(states[1].name(), "countdown");
+    assert_eq!(states[2], MonitorState::Recovered { outage: Duration::from_secs(20) });
+}
+
+#[test]
+fn endless_countdowns_do_not_overflow_the_deadline() {
+    let start = Instant::now();
+    let p = StatePolicy { countdown: Some(Duration::MAX), ..policy(1, 1, 1) };
+    let states = run(&p, start, &[false, false]);
+    assert_eq!(states[1].name(), "countdown");
+}
+
+#[test]
+fn availability_policy_escalates_on_low_availability_only() {
+    let start = Instant::now();
+    let p = StatePolicy { decision: DecisionPolicy::Availability, countdown: Some(Duration::from_secs(30)), ..policy(1, 1, 1) };
+    let step = |state, ok, low, secs| {
+        let input = CycleInput { ok, at: start + Duration::from_secs(secs), hold: None, availability_low: low };
+        transition(state, input, &p).state
+    };
+    // Failed cycles are suspect, however many, while availability holds up
+    let state = step(MonitorState::Normal, false, false, 0);
+    let state = step(state, false, false, 10);
+    assert_eq!(state, MonitorState::Suspect { failed: 2, since: start });
+    // The first failed cycle with availability low makes the action due
+    let state = step(state, false, true, 20);
+    assert_eq!(state, MonitorState::Countdown { failed: 3, since: start, deadline: start + Duration::from_secs(50) });
+    assert_eq!(step(state, true, true, 30), MonitorState::Recovered { outage: Duration::from_secs(30) });
+
+    // After an action, failed checks keep the outage open until availability is low again
+    let state = step(MonitorState::Acting { failed: 3, since: start }, false, false, 40);
+    let state = step(state, false, false, 50);
+    assert!(matches!(state, MonitorState::Emergency { failed: 5, .. }));
+    assert_eq!(step(state, false, true, 60).name(), "countdown");
+    // A successful cycle never escalates, even with availability low
+    assert_eq!(step(MonitorState::Normal, true, true, 0), MonitorState::Normal);
+}
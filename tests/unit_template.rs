@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+
+use minijinja::context;
+use pingdown::runtime::Metrics;
+use pingdown::template::{template_names, Templates};
+
+fn overrides(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+    entries.iter().map(|(name, source)| (name.to_string(), source.to_string())).collect()
+}
+
+#[test]
+fn built_in_templates_reproduce_the_stock_output() {
+    colored::control::set_override(false);
+    let templates = Templates::default();
+    let line = templates.render("normal", context! { up => 2, down => 0, next => 30 });
+    assert_eq!(line, "[NORMAL] OK | up: 2 | down: 0 | next: 30s");
+
+    let ctx = context! {
+        color => "red", label => "DOWN", up => 0, down => 2, failed => 3, recovered => 0,
+        recover_threshold => 2, stage => "1/2 warn", root_cause => "gw", next => 5,
+    };
+    assert_eq!(
+        templates.render("emergency", ctx),
+        "[EMERGENCY] DOWN | up: 0 | down: 2 | failed cycles: 3 | recovered cycles: 0/2 | stage: 1/2 warn | root cause: gw | next: 5s"
+    );
+    let ctx = context! {
+        policy => "retries", failed => 2, interval => 20, retries_left => 3, max_outage => (), root_cause => "gw",
+    };
+    assert_eq!(
+        templates.render("emergency-enter", ctx),
+        "[EMERGENCY] Connection lost after 2 failed cycle(s). Entering emergency loop (20s interval, 3 tries).\n[EMERGENCY] Root cause: gw down."
+    );
+    let ctx = context! { policy => "max-outage", retries_left => 0, remaining => 40, outage_secs => 80 };
+    assert_eq!(templates.render("emergency-remaining", ctx), "[EMERGENCY] 40s remaining (down for 80s)...");
+    let ctx = context! { policy => "retries", actions => vec!["poweroff"], outage_secs => 80 };
+    assert_eq!(templates.render("action", ctx), "[EMERGENCY] Exceeded maximum retries. Running 1 action(s)...");
+    assert_eq!(
+        templates.render("recovery", context! { outage_secs => 95, interval => 20 }),
+        "[EMERGENCY] Reconnected after 95s.\n[EMERGENCY] Exiting 20sec emergency loop..."
+    );
+    let ctx = context! { up => 1, down => 1, failed => 1, fail_threshold => 3, root_cause => "", next => 10 };
+    assert_eq!(templates.render("degraded", ctx), "[NORMAL] DEGRADED | up: 1 | down: 1 | failed cycles: 1/3 | next: 10s");
+
+    let ctx = context! {
+        color => "green", label => "OK", up => 3, down => 0, availability => 99.25, threshold => 95, cycles => 40, next => 60,
+    };
+    assert_eq!(
+        templates.render("availability", ctx),
+        "[NORMAL] OK | up: 3 | down: 0 | availability: 99.2%/95% over 40 cycle(s) | next: 60s"
+    );
+
+    let ctx = context! { outage_secs => 200, actions => vec!["wifi-restart", "reboot"], remaining => 65 };
+    assert_eq!(
+        templates.render("wall-countdown", ctx),
+        "pingdown: network connectivity has been down for 200s. This machine will run wifi-restart -> reboot in 1m05s. Save your work."
+    );
+    assert_eq!(
+        Metrics::new().summary_string(),
+        "Summary -> up: 0 | down: 0 | normal_loops: 0 | emergency_loops: 0"
+    );
+    let metrics = Metrics { total_stage_escalations: Some(2), ..Metrics::new() };
+    assert_eq!(
+        metrics.summary_string(),
+        "Summary -> up: 0 | down: 0 | normal_loops: 0 | emergency_loops: 0 | escalations: 2"
+    );
+}
+
+#[test]
+fn overrides_replace_only_the_named_templates() {
+    colored::control::set_override(false);
+    let templates = Templates::new(overrides(&[(
+        "normal",
+        "{{ host }} ok{% for t in targets %} {{ t.target }}={{ t.rtt_ms }}{% endfor %}",
+    )]))
+    .unwrap();
+    let ctx = context! { host => "box", targets => vec![context! { target => "gw", rtt_ms => 1.5 }] };
+    assert_eq!(templates.render("normal", ctx), "box ok gw=1.5");
+    // The emergency line can show what is left before the actions run
+    let templates = Templates::new(overrides(&[("emergency", "{{ label }} {{ retries_left }} tries, {{ remaining }}s")])).unwrap();
+    let ctx = context! { label => "DOWN", retries_left => 2, remaining => 45 };
+    assert_eq!(templates.render("emergency", ctx), "DOWN 2 tries, 45s");
+    assert_eq!(templates.render("wall-cancel", context! { reason => "network is back" }), "pingdown: pending shutdown cancelled, network is back.");
+
+    // A template that fails while rendering falls back to the built-in one
+    let templates = Templates::new(overrides(&[("summary", "{{ up | style(\"sparkly\") }}")])).unwrap();
+    assert_eq!(
+        Metrics::new().summary(&templates),
+        "Summary -> up: 0 | down: 0 | normal_loops: 0 | emergency_loops: 0"
+    );
+}
+
+#[test]
+fn unknown_names_and_broken_templates_are_rejected() {
+    assert!(template_names().any(|name| name == "mail-body"));
+    assert_eq!(Templates::new(overrides(&[("banner", "hi")])).unwrap_err().0, "banner");
+    assert_eq!(Templates::new(overrides(&[("normal", "{{ up ")])).unwrap_err().0, "normal");
+    assert_eq!(Templates::new(overrides(&[("countdown", "")])).unwrap_err().0, "countdown");
+}
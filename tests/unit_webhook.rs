@@ -0,0 +1,167 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use pingdown::notify::{Notification, Notifier, NotifyEvent};
+use pingdown::ping::CycleReport;
+use pingdown::state::MonitorState;
+use pingdown::webhook::{deliver, enqueue, flush_queue, Webhook, WebhookSpec};
+use pingdown::MonitorConfig;
+
+/// Answers each request with the next status in `statuses` and passes the bodies it received back.
+fn receiver(statuses: Vec<u16>) -> (String, mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for status in statuses {
+            let Ok((stream, _)) = listener.accept() else { return };
+            let mut reader = BufReader::new(stream);
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            let _ = tx.send(String::from_utf8(body).unwrap());
+            let reply = format!("HTTP/1.1 {} X\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+            reader.get_mut().write_all(reply.as_bytes()).unwrap();
+        }
+    });
+    (url, rx)
+}
+
+fn quick(url: &str) -> Webhook {
+    Webhook { timeout: Duration::from_secs(2), backoff: Duration::from_millis(10), ..Webhook::new(url) }
+}
+
+#[test]
+fn webhook_specs_are_validated_per_field() {
+    let spec = WebhookSpec { url: "https://example.com/hook".to_string(), timeout: Some(5), retries: Some(0) };
+    let hook = Webhook::parse(&spec).unwrap();
+    assert_eq!(hook.timeout, Duration::from_secs(5));
+    assert_eq!(hook.retries, 0);
+
+    let spec = WebhookSpec { url: "ftp://example.com".to_string(), ..WebhookSpec::default() };
+    assert_eq!(Webhook::parse(&spec).unwrap_err().0, "url");
+
+    let spec = WebhookSpec { url: "http://example.com".to_string(), timeout: Some(0), ..WebhookSpec::default() };
+    assert_eq!(Webhook::parse(&spec).unwrap_err().0, "timeout");
+}
+
+#[test]
+fn failed_posts_are_retried_until_one_is_accepted() {
+    let (url, bodies) = receiver(vec![500, 503, 200]);
+    deliver(&quick(&url), "{\"event\":\"emergency\"}").unwrap();
+    let received: Vec<String> = bodies.try_iter().collect();
+    assert_eq!(received, vec!["{\"event\":\"emergency\"}"; 3]);
+
+    let (url, _bodies) = receiver(vec![500, 500]);
+    let hook = Webhook { retries: 1, ..quick(&url) };
+    assert!(deliver(&hook, "{}").unwrap_err().to_string().contains("500"));
+
+    // A rejection is final, so it is not retried
+    let (url, bodies) = receiver(vec![404, 200]);
+    let err = deliver(&quick(&url), "{}").unwrap_err();
+    assert!(!err.retryable, "{}", err);
+    assert_eq!(bodies.try_iter().count(), 1);
+}
+
+#[test]
+fn undelivered_notifications_wait_in_the_queue() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let queue = dir.path().join("webhooks.jsonl");
+    // Nothing listens on a port that was just released
+    let closed = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}/hook", listener.local_addr().unwrap())
+    };
+    let hook = Webhook { retries: 0, ..quick(&closed) };
+    assert!(deliver(&hook, "{\"n\":1}").is_err());
+    enqueue(&queue, &hook, "{\"n\":1}").unwrap();
+    assert_eq!(flush_queue(&queue), 0);
+    assert!(queue.exists());
+
+    let (url, bodies) = receiver(vec![200]);
+    enqueue(&queue, &quick(&url), "{\"n\":2}").unwrap();
+    assert_eq!(flush_queue(&queue), 1);
+    assert_eq!(bodies.recv_timeout(Duration::from_secs(2)).unwrap(), "{\"n\":2}");
+    // The entry for the closed port stays queued
+    let kept = std::fs::read_to_string(&queue).unwrap();
+    assert_eq!(kept.lines().count(), 1);
+    assert!(kept.contains(&closed));
+
+    // Rejected and malformed deliveries are dropped instead of being re-sent forever
+    let (url, bodies) = receiver(vec![400]);
+    enqueue(&queue, &quick(&url), "{\"n\":3}").unwrap();
+    enqueue(&queue, &quick("nope://hook"), "{\"n\":4}").unwrap();
+    assert_eq!(flush_queue(&queue), 0);
+    assert_eq!(bodies.recv_timeout(Duration::from_secs(2)).unwrap(), "{\"n\":3}");
+    let kept = std::fs::read_to_string(&queue).unwrap();
+    assert_eq!(kept.lines().count(), 1);
+    assert!(kept.contains(&closed));
+}
+
+#[test]
+fn a_successful_check_sends_the_queue_without_a_new_event() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let queue = dir.path().join("webhooks.jsonl");
+    let (url, bodies) = receiver(vec![200]);
+    let cfg = MonitorConfig {
+        webhooks: vec![quick(&url)],
+        webhook_queue: Some(queue.clone()),
+        ..MonitorConfig::default()
+    };
+    let notifier = Notifier::start(&cfg);
+    enqueue(&queue, &quick(&url), "{\"n\":1}").unwrap();
+
+    notifier.connected();
+    assert_eq!(bodies.recv_timeout(Duration::from_secs(2)).unwrap(), "{\"n\":1}");
+    // The worker removes the emptied queue after sending
+    for _ in 0..40 {
+        if !queue.exists() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    assert!(!queue.exists());
+}
+
+#[test]
+fn payload_describes_the_outage_and_each_target() {
+    let cfg = MonitorConfig {
+        targets: vec!["gw".to_string(), "1.1.1.1".to_string(), "8.8.8.8".to_string()],
+        ..MonitorConfig::default()
+    };
+    let report = CycleReport {
+        ok: false,
+        succeeds: 1,
+        failures: 1,
+        down: vec!["gw".to_string()],
+        unreachable: vec![("1.1.1.1".to_string(), "gw".to_string())],
+        rtts: vec![("8.8.8.8".to_string(), Duration::from_micros(12_500))],
+    };
+    let state = MonitorState::Recovered { outage: Duration::from_secs(90) };
+    let notification = Notification::new(NotifyEvent::Recovery, state, &cfg, Some(&report), "box");
+    let json: serde_json::Value = serde_json::to_value(&notification).unwrap();
+    assert_eq!(json["event"], "recovery");
+    assert_eq!(json["host"], "box");
+    assert_eq!(json["state"], "recovered");
+    assert_eq!(json["outage_secs"], 90);
+    assert!(json["outage_start"].is_string());
+    assert_eq!(json["targets"][0]["status"], "down");
+    assert_eq!(json["targets"][1]["status"], "unreachable");
+    assert_eq!(json["targets"][1]["root_cause"], "gw");
+    assert_eq!(json["targets"][2]["status"], "up");
+    assert_eq!(json["targets"][2]["rtt_ms"], 12.5);
+    assert!(json["targets"][0].get("rtt_ms").is_none());
+}